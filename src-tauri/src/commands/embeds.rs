@@ -0,0 +1,187 @@
+// src-tauri/src/commands/embeds.rs
+// ![[笔记]]、![[笔记#标题]] 与 ![[图片.png]] 嵌入 (transclusion)
+
+use crate::commands::links::{is_attachment_target, is_image_target, link_note_name};
+use crate::commands::outline::{extract_headings, slugify};
+use crate::commands::path_utils::to_absolute_path;
+use crate::commands::properties::split_frontmatter;
+use crate::commands::special_blocks::ProtectedBlocks;
+use crate::commands::utils::{escape_html, markdown_options, render_markdown_html, resolve_wikilink, RenderContext};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag};
+use regex::Regex;
+use rusqlite::Connection;
+use std::ops::Range;
+use std::path::{Component, Path};
+
+/// 嵌入的最大嵌套层数 (不含当前笔记)
+const MAX_EMBED_DEPTH: usize = 3;
+
+static EMBED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"!\[\[([^\]]+)\]\]").unwrap());
+
+fn embed_error(class: &str, message: &str) -> String {
+    format!("<div class=\"embed embed-error {}\">{}</div>", class, escape_html(message))
+}
+
+/// 截取标题所在的章节：从该标题开始，到下一个同级或更高级标题为止
+/// 标题按文本 (忽略大小写) 或锚点 id 匹配
+fn extract_section<'a>(content: &'a str, heading: &str) -> Option<&'a str> {
+    let headings = extract_headings(content);
+    let wanted_slug = slugify(heading);
+    let index = headings
+        .iter()
+        .position(|h| h.text.eq_ignore_ascii_case(heading) || h.id == wanted_slug)?;
+    let start = &headings[index];
+    let end = headings[index + 1..]
+        .iter()
+        .find(|h| h.level <= start.level)
+        .map(|h| h.byte_start)
+        .unwrap_or(content.len());
+    Some(&content[start.byte_start..end])
+}
+
+/// 定位图片附件：依次尝试 笔记所在目录、工作区根目录、数据库中记录的各个文件夹 (浅层优先)
+/// 名称中含 .. 或绝对路径时不解析，避免读取工作区之外的文件
+fn locate_attachment(conn: &Connection, root_path: &str, source_path: Option<&str>, name: &str) -> Option<String> {
+    let root = Path::new(root_path);
+    let name = name.trim_start_matches('/');
+    if !Path::new(name).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    if let Some(dir) = source_path.and_then(|p| Path::new(p).parent()) {
+        if !dir.as_os_str().is_empty() {
+            candidates.push(dir.join(name));
+        }
+    }
+    candidates.push(Path::new(name).to_path_buf());
+    for candidate in candidates {
+        if to_absolute_path(root, &candidate).is_file() {
+            return Some(candidate.to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    // 附件不在 files 表中，按文件名在已知的文件夹中查找，而不是遍历整个工作区
+    let file_name = Path::new(name).file_name()?;
+    let mut stmt = conn
+        .prepare("SELECT path FROM files WHERE is_dir = 1 ORDER BY length(path) - length(replace(path, '/', '')), path")
+        .ok()?;
+    let folders: Vec<String> = stmt.query_map([], |row| row.get(0)).ok()?.filter_map(Result::ok).collect();
+    folders
+        .iter()
+        .map(|folder| Path::new(folder).join(file_name))
+        .find(|candidate| to_absolute_path(root, candidate).is_file())
+        .map(|candidate| candidate.to_string_lossy().replace('\\', "/"))
+}
+
+/// 图片嵌入：`![[image.png|300]]` 中的数字视为宽度，其余视为替代文本
+/// 输出 data-path (相对工作区)，由前端转换为可访问的地址
+fn render_image_embed(ctx: &RenderContext, name: &str, option: Option<&str>, source_path: Option<&str>) -> String {
+    let Some(root_path) = ctx.root_path.as_deref() else {
+        return embed_error("embed-missing", &format!("工作区未打开，无法嵌入: {}", name));
+    };
+    let Some(path) = locate_attachment(ctx.conn, root_path, source_path, name) else {
+        return embed_error("embed-missing", &format!("未找到附件: {}", name));
+    };
+
+    let (width, alt) = match option {
+        Some(o) if o.chars().all(|c| c.is_ascii_digit()) => (Some(o), name),
+        Some(o) => (None, o),
+        None => (None, name),
+    };
+    let width_attr = width.map(|w| format!(" width=\"{}\"", w)).unwrap_or_default();
+    format!(
+        "<img class=\"embed embed-image\" data-path=\"{}\" alt=\"{}\"{}>",
+        escape_html(&path),
+        escape_html(alt),
+        width_attr
+    )
+}
+
+/// 笔记嵌入：递归渲染被嵌入的笔记 (或其中一节)
+fn render_note_embed(ctx: &mut RenderContext, name: &str, heading: Option<&str>, display: &str) -> String {
+    let Some(path) = resolve_wikilink(ctx.conn, name) else {
+        return embed_error("embed-missing", &format!("未找到笔记: {}", name));
+    };
+    if ctx.embed_stack.contains(&path) {
+        return embed_error("embed-cycle", &format!("循环嵌入: {}", display));
+    }
+    if ctx.embed_stack.len() > MAX_EMBED_DEPTH {
+        return embed_error("embed-depth", &format!("嵌入层数超过 {} 层: {}", MAX_EMBED_DEPTH, display));
+    }
+    let Some(root_path) = ctx.root_path.clone() else {
+        return embed_error("embed-missing", &format!("工作区未打开，无法嵌入: {}", display));
+    };
+
+    let absolute_path = to_absolute_path(Path::new(&root_path), Path::new(&path));
+    let content = match std::fs::read_to_string(&absolute_path) {
+        Ok(c) => c,
+        Err(e) => return embed_error("embed-missing", &format!("读取笔记失败 ({}): {}", path, e)),
+    };
+    let body_start = split_frontmatter(&content).map(|(_, start)| start).unwrap_or(0);
+    let body = &content[body_start..];
+    let section = match heading {
+        Some(h) => match extract_section(body, h) {
+            Some(section) => section,
+            None => return embed_error("embed-missing", &format!("未找到标题: {}", display)),
+        },
+        None => body,
+    };
+
+    ctx.embed_stack.push(path.clone());
+    let inner_html = render_markdown_html(ctx, section, Some(&path));
+    ctx.embed_stack.pop();
+
+    format!(
+        "<div class=\"embed embed-note\" data-path=\"{path}\"><div class=\"embed-title\"><a href=\"#\" class=\"internal-link\" data-path=\"{path}\">{title}</a></div><div class=\"embed-content\">{inner}</div></div>\n",
+        path = escape_html(&path),
+        title = escape_html(display),
+        inner = inner_html
+    )
+}
+
+/// 代码所在的字节范围 (围栏代码块、缩进代码块与行内代码)
+fn code_ranges(content: &str) -> Vec<Range<usize>> {
+    Parser::new_ext(content, markdown_options())
+        .into_offset_iter()
+        .filter(|(event, _)| matches!(event, Event::Start(Tag::CodeBlock(_)) | Event::Code(_)))
+        .map(|(_, range)| range)
+        .collect()
+}
+
+/// 将内容中的 ![[ ]] 替换为占位符，渲染结果存入 blocks，在 HTML 过滤后还原
+/// 代码中的 ![[ ]] 是示例文本，原样保留
+pub fn expand_embeds(
+    ctx: &mut RenderContext,
+    content: &str,
+    source_path: Option<&str>,
+    blocks: &mut ProtectedBlocks,
+) -> String {
+    let code = code_ranges(content);
+    EMBED_RE
+        .replace_all(content, |caps: &regex::Captures| {
+            let whole = caps.get(0).unwrap();
+            if code.iter().any(|range| range.contains(&whole.start())) {
+                return whole.as_str().to_string();
+            }
+            let target = caps[1].trim();
+            let (link, option) = match target.split_once('|') {
+                Some((link, option)) => (link.trim(), Some(option.trim())),
+                None => (target, None),
+            };
+            let name = link_note_name(link);
+
+            let html = if is_image_target(name) {
+                render_image_embed(ctx, name, option, source_path)
+            } else if is_attachment_target(name) {
+                embed_error("embed-unsupported", &format!("不支持嵌入此类型的附件: {}", name))
+            } else {
+                let heading = link.split_once('#').map(|(_, h)| h.trim()).filter(|h| !h.is_empty());
+                let note_name = name.strip_suffix(".md").unwrap_or(name);
+                render_note_embed(ctx, note_name, heading, option.unwrap_or(link))
+            };
+            blocks.push_html(&html)
+        })
+        .to_string()
+}
@@ -2,7 +2,7 @@
 // ★★★ 已根据新的 SaveTracker (app_activity_locks) 重构 ★★★
 
 use crate::commands::history::record_file_event;
-use crate::commands::links::update_links_for_file;
+use crate::commands::links::{rewrite_links_after_move, update_links_for_file};
 use crate::commands::path_utils::{to_absolute_path, to_relative_path};  
 use crate::AppState;
 use rusqlite::{params, Connection};
@@ -106,6 +106,7 @@ pub async fn move_item(
     println!("   [fs::move_item] ✅ 数据库更新完成 (移动)");
 
     // --- 9. 分发索引任务 (锁将在后台释放) ---
+    let moves: Vec<(String, String)> = affected_files.iter().cloned().zip(new_affected_files.iter().cloned()).collect();
     for (old_file_path, new_file_path) in affected_files.into_iter().zip(new_affected_files.into_iter()) {
         if let Err(e) = indexing_jobs::dispatch_rename_job(
             root_path.clone(),
@@ -120,6 +121,13 @@ pub async fn move_item(
         }
     }
     
+    // --- 10. 更新指向被移动文件 (以及被移动笔记中) 的相对 Markdown 链接 ---
+    match rewrite_links_after_move(&root_path, &moves, state).await {
+        Ok(count) if count > 0 => println!("   [fs::move_item] 🔗 已更新 {} 个笔记中的 Markdown 链接", count),
+        Ok(_) => {}
+        Err(e) => eprintln!("⚠️ [fs::move_item] 更新 Markdown 链接失败: {}", e),
+    }
+
     println!("✅ [fs::move_item] 移动完成: {} -> {}", source_path, new_relative_path);
 
     // ★★★ 3. (重构) 更改返回值以匹配前端期望 ★★★
@@ -319,6 +327,20 @@ pub async fn create_new_file(
     relative_dir_path: String,
     file_name: String,
     state: State<'_, AppState>,
+) -> Result<String, String> {
+    let title = file_name.trim_end_matches(".md").to_string();
+    let initial_content = format!("# {}\n\n", title);
+    create_file_with_content(root_path, relative_dir_path, file_name, initial_content, state).await
+}
+
+/// 以给定的初始内容新建笔记 (加锁、入库、历史记录、分发索引)
+/// create_new_file 和周期笔记等需要模板内容的功能共用此流程
+pub async fn create_file_with_content(
+    root_path: String,
+    relative_dir_path: String,
+    file_name: String,
+    initial_content: String,
+    state: State<'_, AppState>,
 ) -> Result<String, String> {
     let base_path = Path::new(&root_path);
     let absolute_dir_path = to_absolute_path(base_path, Path::new(&relative_dir_path));
@@ -333,7 +355,6 @@ pub async fn create_new_file(
     }
     
     // ★★★ 核心修改：计算元数据 ★★★
-    let initial_content = format!("# {}\n\n", file_name_str.trim_end_matches(".md"));
     let content_size = initial_content.len() as i64;
     let word_count = initial_content.split_whitespace().count() as i64;
     
@@ -671,6 +692,7 @@ pub async fn rename_item(
     // (L1/L2 锁*不*在这里释放)
 
     // 8. 分发重命名索引任务 (锁将在后台释放)
+    let moves: Vec<(String, String)> = affected_files.iter().cloned().zip(new_affected_files.iter().cloned()).collect();
     for (old_file_path, new_file_path) in affected_files.into_iter().zip(new_affected_files.into_iter()) {
         println!("  📄 {} -> {}", old_file_path, new_file_path);
         
@@ -687,6 +709,13 @@ pub async fn rename_item(
         }
     }
     
+    // 9. 更新指向被重命名文件 (以及被移动笔记中) 的相对 Markdown 链接
+    match rewrite_links_after_move(&root_path, &moves, state).await {
+        Ok(count) if count > 0 => println!("🔗 已更新 {} 个笔记中的 Markdown 链接", count),
+        Ok(_) => {}
+        Err(e) => eprintln!("⚠️ 更新 Markdown 链接失败: {}", e),
+    }
+
     println!("✅ 重命名完成（索引正在后台更新）");
 
     Ok(RenameResult {
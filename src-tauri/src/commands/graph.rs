@@ -0,0 +1,604 @@
+// src-tauri/src/commands/graph.rs
+// 局部关系图 (N 跳邻域)、笔记之间的最短链接路径与关系图指标 (PageRank、连通分量、社区)
+
+use crate::AppState;
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use tauri::{command, State};
+
+/// 邻域的最大跳数
+const MAX_GRAPH_DEPTH: usize = 5;
+
+/// 返回的笔记节点上限，超过时截断 (truncated = true)
+const MAX_GRAPH_NODES: usize = 2000;
+
+/// PageRank 参数
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-9;
+
+/// 标签传播 (社区划分) 的最大轮数
+const COMMUNITY_MAX_ITERATIONS: usize = 20;
+
+/// 默认返回的核心笔记数
+const DEFAULT_HUB_LIMIT: usize = 20;
+
+/// 指标计算结果缓存，键为 笔记 id 与链接 的哈希，链接变化后自动失效
+type MetricsCacheEntry = (u64, Arc<ComputedMetrics>);
+static METRICS_CACHE: Lazy<Mutex<Option<MetricsCacheEntry>>> = Lazy::new(|| Mutex::new(None));
+
+/// 笔记之间的链接 (忽略链接类型，同一对笔记只保留一条)
+pub struct LinkGraph {
+    pub outgoing: HashMap<i64, Vec<i64>>,
+    pub incoming: HashMap<i64, Vec<i64>>,
+}
+
+impl LinkGraph {
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT source_file_id, target_file_id FROM links WHERE source_file_id != target_file_id")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| e.to_string())?;
+        let mut graph = LinkGraph {
+            outgoing: HashMap::new(),
+            incoming: HashMap::new(),
+        };
+        for row in rows {
+            let (from, to) = row.map_err(|e| e.to_string())?;
+            graph.outgoing.entry(from).or_default().push(to);
+            graph.incoming.entry(to).or_default().push(from);
+        }
+        Ok(graph)
+    }
+
+    /// 相邻笔记；directed 为 false 时同时包含入链
+    pub fn neighbours(&self, id: i64, directed: bool) -> impl Iterator<Item = i64> + '_ {
+        let outgoing = self.outgoing.get(&id).into_iter().flatten();
+        let incoming = self.incoming.get(&id).into_iter().flatten().filter(move |_| !directed);
+        outgoing.chain(incoming).copied()
+    }
+}
+
+/// 笔记的基本信息 (不含文件夹条目)
+struct NoteInfo {
+    path: String,
+    title: String,
+}
+
+fn load_notes(conn: &Connection) -> Result<HashMap<i64, NoteInfo>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, path, title FROM files WHERE is_dir = 0")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let path: String = row.get(1)?;
+            let title = row.get::<_, Option<String>>(2)?.unwrap_or_else(|| path.clone());
+            Ok((row.get::<_, i64>(0)?, NoteInfo { path, title }))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())
+}
+
+/// 每个笔记的标签 (tag id, 标签名)
+fn load_note_tags(conn: &Connection) -> Result<HashMap<i64, Vec<(i64, String)>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT ft.file_id, t.id, t.name FROM file_tags ft INNER JOIN tags t ON t.id = ft.tag_id ORDER BY t.name")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))
+        .map_err(|e| e.to_string())?;
+    let mut tags: HashMap<i64, Vec<(i64, String)>> = HashMap::new();
+    for row in rows {
+        let (file_id, tag_id, name) = row.map_err(|e| e.to_string())?;
+        tags.entry(file_id).or_default().push((tag_id, name));
+    }
+    Ok(tags)
+}
+
+/// 关系图过滤条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GraphFilters {
+    /// 为笔记的标签添加节点 (笔记 -> 标签)
+    pub include_tags: bool,
+    /// 整体视图 (未指定 path) 时包含没有任何链接的笔记
+    pub include_orphans: bool,
+    /// 只保留这些文件夹 (含子文件夹) 下的笔记
+    pub folders: Vec<String>,
+    /// 只保留带有其中任一标签的笔记
+    pub tags: Vec<String>,
+}
+
+impl GraphFilters {
+    fn accepts(&self, note: &NoteInfo, tags: Option<&Vec<(i64, String)>>) -> bool {
+        let in_folder = self.folders.is_empty()
+            || self.folders.iter().any(|folder| {
+                let folder = folder.trim_matches('/');
+                folder.is_empty() || note.path.starts_with(&format!("{}/", folder))
+            });
+        let has_tag = self.tags.is_empty()
+            || tags.is_some_and(|note_tags| {
+                note_tags
+                    .iter()
+                    .any(|(_, name)| self.tags.iter().any(|t| t.trim_start_matches('#').eq_ignore_ascii_case(name)))
+            });
+        in_folder && has_tag
+    }
+}
+
+/// 节点：笔记节点的 id 为文件 id，标签节点的 id 为标签 id 取负数，两者不会冲突
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalGraphNode {
+    pub id: i64,
+    pub label: String,
+    pub path: Option<String>,
+    pub kind: &'static str, // note / tag
+    pub depth: usize,       // 与中心笔记的跳数 (标签节点为所属笔记的跳数 + 1)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalGraphEdge {
+    pub from: i64,
+    pub to: i64,
+    pub kind: &'static str, // link / tag
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalGraph {
+    pub center: Option<i64>,
+    pub nodes: Vec<LocalGraphNode>,
+    pub edges: Vec<LocalGraphEdge>,
+    pub truncated: bool,
+}
+
+fn file_id_for_path(conn: &Connection, path: &str) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT id FROM files WHERE path = ?1 AND is_dir = 0",
+        [path],
+        |row| row.get(0),
+    )
+    .map_err(|_| format!("笔记不存在: {}", path))
+}
+
+/// 局部关系图：与 path 相距 depth 跳以内 (沿入链和出链) 的笔记
+/// path 为空时返回整个工作区的关系图 (同样受过滤条件限制)
+/// 被过滤掉的笔记不会出现，也不会作为中转继续向外扩展
+#[command]
+pub async fn get_local_graph(
+    path: Option<String>,
+    depth: Option<usize>,
+    filters: Option<GraphFilters>,
+    state: State<'_, AppState>,
+) -> Result<LocalGraph, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let filters = filters.unwrap_or_default();
+    let depth = depth.unwrap_or(1).clamp(1, MAX_GRAPH_DEPTH);
+    let links = LinkGraph::load(&conn)?;
+    let notes = load_notes(&conn)?;
+    let note_tags = if filters.include_tags || !filters.tags.is_empty() {
+        load_note_tags(&conn)?
+    } else {
+        HashMap::new()
+    };
+    let accepts = |id: i64| {
+        notes
+            .get(&id)
+            .is_some_and(|note| filters.accepts(note, note_tags.get(&id)))
+    };
+
+    // 1. 选出笔记节点 (id -> 跳数)
+    let center = path.as_deref().map(|p| file_id_for_path(&conn, p)).transpose()?;
+    let mut selected: HashMap<i64, usize> = HashMap::new();
+    let mut truncated = false;
+    match center {
+        Some(center_id) => {
+            let mut queue = VecDeque::from([(center_id, 0)]);
+            selected.insert(center_id, 0);
+            'bfs: while let Some((id, hops)) = queue.pop_front() {
+                if hops == depth {
+                    continue;
+                }
+                for next in links.neighbours(id, false) {
+                    if selected.contains_key(&next) || !accepts(next) {
+                        continue;
+                    }
+                    if selected.len() >= MAX_GRAPH_NODES {
+                        truncated = true;
+                        break 'bfs;
+                    }
+                    selected.insert(next, hops + 1);
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+        None => {
+            let mut ids: Vec<i64> = notes
+                .keys()
+                .copied()
+                .filter(|id| filters.include_orphans || links.neighbours(*id, false).next().is_some())
+                .filter(|id| accepts(*id))
+                .collect();
+            ids.sort_unstable();
+            truncated = ids.len() > MAX_GRAPH_NODES;
+            selected.extend(ids.into_iter().take(MAX_GRAPH_NODES).map(|id| (id, 0)));
+        }
+    }
+
+    // 2. 节点之间的链接
+    let mut edges: Vec<LocalGraphEdge> = Vec::new();
+    for &id in selected.keys() {
+        let mut targets: Vec<i64> = links
+            .outgoing
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|to| selected.contains_key(to))
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        edges.extend(targets.into_iter().map(|to| LocalGraphEdge { from: id, to, kind: "link" }));
+    }
+
+    let mut nodes: Vec<LocalGraphNode> = selected
+        .iter()
+        .filter_map(|(&id, &hops)| {
+            notes.get(&id).map(|note| LocalGraphNode {
+                id,
+                label: note.title.clone(),
+                path: Some(note.path.clone()),
+                kind: "note",
+                depth: hops,
+            })
+        })
+        .collect();
+    nodes.sort_by_key(|n| (n.depth, n.id));
+
+    // 3. 标签节点
+    if filters.include_tags {
+        let mut tag_nodes: HashMap<i64, LocalGraphNode> = HashMap::new();
+        for (&id, &hops) in &selected {
+            for (tag_id, name) in note_tags.get(&id).into_iter().flatten() {
+                let node = tag_nodes.entry(-tag_id).or_insert_with(|| LocalGraphNode {
+                    id: -tag_id,
+                    label: format!("#{}", name),
+                    path: None,
+                    kind: "tag",
+                    depth: hops + 1,
+                });
+                node.depth = node.depth.min(hops + 1);
+                edges.push(LocalGraphEdge { from: id, to: -tag_id, kind: "tag" });
+            }
+        }
+        let mut tag_nodes: Vec<LocalGraphNode> = tag_nodes.into_values().collect();
+        tag_nodes.sort_by(|a, b| a.label.cmp(&b.label));
+        nodes.extend(tag_nodes);
+    }
+
+    println!(
+        "🕸️ [graph] 局部关系图: {} 个节点, {} 条边{}",
+        nodes.len(),
+        edges.len(),
+        if truncated { " (已截断)" } else { "" }
+    );
+    Ok(LocalGraph { center, nodes, edges, truncated })
+}
+
+/// 两篇笔记之间最短的链接路径 (含首尾)，不连通时返回 None
+/// directed 为 true 时只沿链接方向 (from 链接到 ... 链接到 to) 查找
+#[command]
+pub async fn find_path(
+    from: String,
+    to: String,
+    directed: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Option<Vec<LocalGraphNode>>, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let from_id = file_id_for_path(&conn, &from)?;
+    let to_id = file_id_for_path(&conn, &to)?;
+    let links = LinkGraph::load(&conn)?;
+    let Some(ids) = shortest_path(&links, from_id, to_id, directed.unwrap_or(false)) else {
+        return Ok(None);
+    };
+
+    let notes = load_notes(&conn)?;
+    let path = ids
+        .into_iter()
+        .enumerate()
+        .map(|(hops, id)| {
+            let note = notes.get(&id);
+            LocalGraphNode {
+                id,
+                label: note.map(|n| n.title.clone()).unwrap_or_default(),
+                path: note.map(|n| n.path.clone()),
+                kind: "note",
+                depth: hops,
+            }
+        })
+        .collect();
+    Ok(Some(path))
+}
+
+/// 广度优先搜索最短路径
+pub fn shortest_path(links: &LinkGraph, from: i64, to: i64, directed: bool) -> Option<Vec<i64>> {
+    let mut previous: HashMap<i64, i64> = HashMap::new();
+    let mut visited: HashSet<i64> = HashSet::from([from]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(id) = queue.pop_front() {
+        if id == to {
+            let mut path = vec![to];
+            let mut current = to;
+            while let Some(&prev) = previous.get(&current) {
+                path.push(prev);
+                current = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in links.neighbours(id, directed) {
+            if visited.insert(next) {
+                previous.insert(next, id);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// 单个笔记的指标 (按笔记 id)
+#[derive(Debug, Clone, Copy)]
+struct NodeScores {
+    in_degree: usize,
+    out_degree: usize,
+    pagerank: f64,
+    component: usize,
+    community: usize,
+}
+
+struct ComputedMetrics {
+    scores: HashMap<i64, NodeScores>,
+    component_count: usize,
+    community_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeMetrics {
+    pub id: i64,
+    pub path: String,
+    pub title: String,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    pub pagerank: f64,
+    pub component: usize, // 连通分量编号 (忽略链接方向)，按大小降序从 0 开始
+    pub community: usize, // 社区编号，按大小降序从 0 开始
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphMetrics {
+    pub nodes: Vec<NodeMetrics>,
+    pub hubs: Vec<NodeMetrics>, // PageRank 最高且有链接的笔记
+    pub component_count: usize,
+    pub community_count: usize,
+}
+
+/// 将分组标签按组大小降序重新编号 (大小相同时按最小节点 id)
+fn renumber_groups(labels: &HashMap<i64, i64>) -> (HashMap<i64, usize>, usize) {
+    let mut groups: HashMap<i64, (usize, i64)> = HashMap::new();
+    for (&id, &label) in labels {
+        let entry = groups.entry(label).or_insert((0, id));
+        entry.0 += 1;
+        entry.1 = entry.1.min(id);
+    }
+    let mut order: Vec<(i64, (usize, i64))> = groups.into_iter().collect();
+    order.sort_by_key(|(_, (size, min_id))| (std::cmp::Reverse(*size), *min_id));
+    let numbering: HashMap<i64, usize> = order.iter().enumerate().map(|(i, (label, _))| (*label, i)).collect();
+    let renumbered = labels.iter().map(|(&id, label)| (id, numbering[label])).collect();
+    (renumbered, order.len())
+}
+
+/// 连通分量 (忽略链接方向)，返回 节点 -> 分量中最小的节点 id
+fn connected_components(ids: &[i64], links: &LinkGraph) -> HashMap<i64, i64> {
+    let mut labels: HashMap<i64, i64> = HashMap::new();
+    for &start in ids {
+        if labels.contains_key(&start) {
+            continue;
+        }
+        labels.insert(start, start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(id) = queue.pop_front() {
+            for next in links.neighbours(id, false) {
+                if let Entry::Vacant(entry) = labels.entry(next) {
+                    entry.insert(start);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    labels
+}
+
+/// 标签传播划分社区：每个节点反复采用邻居中最常见的标签 (相同时取较小者)，
+/// 按 id 顺序原地更新，结果是确定的
+fn label_propagation(ids: &[i64], links: &LinkGraph) -> HashMap<i64, i64> {
+    let mut labels: HashMap<i64, i64> = ids.iter().map(|&id| (id, id)).collect();
+    for _ in 0..COMMUNITY_MAX_ITERATIONS {
+        let mut changed = false;
+        for &id in ids {
+            let mut counts: HashMap<i64, usize> = HashMap::new();
+            for next in links.neighbours(id, false) {
+                if let Some(&label) = labels.get(&next) {
+                    *counts.entry(label).or_default() += 1;
+                }
+            }
+            let best = counts
+                .into_iter()
+                .max_by_key(|&(label, count)| (count, std::cmp::Reverse(label)))
+                .map(|(label, _)| label);
+            if let Some(best) = best {
+                if labels[&id] != best {
+                    labels.insert(id, best);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    labels
+}
+
+/// PageRank (悬挂节点的权重平均分给所有节点)
+fn pagerank(ids: &[i64], links: &LinkGraph) -> HashMap<i64, f64> {
+    let n = ids.len();
+    if n == 0 {
+        return HashMap::new();
+    }
+    let index: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let targets: Vec<Vec<usize>> = ids
+        .iter()
+        .map(|id| {
+            links
+                .outgoing
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter_map(|to| index.get(to).copied())
+                .collect()
+        })
+        .collect();
+
+    let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+    let mut ranks = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_MAX_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|&i| targets[i].is_empty()).map(|i| ranks[i]).sum();
+        let mut next = vec![base + PAGERANK_DAMPING * dangling / n as f64; n];
+        for (i, outs) in targets.iter().enumerate() {
+            let share = PAGERANK_DAMPING * ranks[i] / outs.len().max(1) as f64;
+            for &j in outs {
+                next[j] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    ids.iter().copied().zip(ranks).collect()
+}
+
+fn compute_metrics(ids: &[i64], links: &LinkGraph) -> ComputedMetrics {
+    let ranks = pagerank(ids, links);
+    let (components, component_count) = renumber_groups(&connected_components(ids, links));
+    let (communities, community_count) = renumber_groups(&label_propagation(ids, links));
+    let scores = ids
+        .iter()
+        .map(|&id| {
+            let degree = |map: &HashMap<i64, Vec<i64>>| map.get(&id).map_or(0, Vec::len);
+            (
+                id,
+                NodeScores {
+                    in_degree: degree(&links.incoming),
+                    out_degree: degree(&links.outgoing),
+                    pagerank: ranks[&id],
+                    component: components[&id],
+                    community: communities[&id],
+                },
+            )
+        })
+        .collect();
+    ComputedMetrics {
+        scores,
+        component_count,
+        community_count,
+    }
+}
+
+/// 关系图指标：出入度、PageRank、连通分量与社区，以及核心笔记 (hub)
+/// 计算结果会被缓存，笔记或链接变化后重新计算
+#[command]
+pub async fn get_graph_metrics(hub_limit: Option<usize>, state: State<'_, AppState>) -> Result<GraphMetrics, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let notes = load_notes(&conn)?;
+    let mut links = LinkGraph::load(&conn)?;
+    // 只保留两端都是笔记的链接，并排序使哈希与计算结果稳定
+    for targets in links.outgoing.values_mut().chain(links.incoming.values_mut()) {
+        targets.retain(|id| notes.contains_key(id));
+        targets.sort_unstable();
+    }
+    let mut ids: Vec<i64> = notes.keys().copied().collect();
+    ids.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    ids.hash(&mut hasher);
+    for id in &ids {
+        links.outgoing.get(id).hash(&mut hasher);
+    }
+    let key = hasher.finish();
+
+    let cached = METRICS_CACHE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|(cached_key, _)| *cached_key == key)
+        .map(|(_, metrics)| metrics.clone());
+    let metrics = match cached {
+        Some(metrics) => metrics,
+        None => {
+            let metrics = Arc::new(compute_metrics(&ids, &links));
+            println!(
+                "🕸️ [graph] 重新计算关系图指标: {} 个笔记, {} 个连通分量, {} 个社区",
+                ids.len(),
+                metrics.component_count,
+                metrics.community_count
+            );
+            *METRICS_CACHE.lock().unwrap() = Some((key, metrics.clone()));
+            metrics
+        }
+    };
+
+    let nodes: Vec<NodeMetrics> = ids
+        .iter()
+        .filter_map(|id| {
+            let (note, scores) = (notes.get(id)?, metrics.scores.get(id)?);
+            Some(NodeMetrics {
+                id: *id,
+                path: note.path.clone(),
+                title: note.title.clone(),
+                in_degree: scores.in_degree,
+                out_degree: scores.out_degree,
+                pagerank: scores.pagerank,
+                component: scores.component,
+                community: scores.community,
+            })
+        })
+        .collect();
+    let mut hubs: Vec<NodeMetrics> = nodes
+        .iter()
+        .filter(|n| n.in_degree + n.out_degree > 0)
+        .cloned()
+        .collect();
+    hubs.sort_by(|a, b| b.pagerank.total_cmp(&a.pagerank).then_with(|| a.id.cmp(&b.id)));
+    hubs.truncate(hub_limit.unwrap_or(DEFAULT_HUB_LIMIT));
+
+    Ok(GraphMetrics {
+        nodes,
+        hubs,
+        component_count: metrics.component_count,
+        community_count: metrics.community_count,
+    })
+}
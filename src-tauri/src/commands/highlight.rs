@@ -0,0 +1,242 @@
+// src-tauri/src/commands/highlight.rs
+// 代码块语法高亮 (syntect 内置语法，输出 class，由主题 CSS 着色)
+
+use crate::commands::utils::escape_html;
+use crate::database::{get_setting, set_setting};
+use crate::AppState;
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+use tauri::{command, State};
+
+const SETTING_KEY: &str = "render.highlight";
+
+/// 所有高亮 class 统一加 `hl-` 前缀，避免与页面样式冲突
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// 高亮结果缓存的最大条目数，超过后整体清空
+const MAX_CACHE_ENTRIES: usize = 512;
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+static HIGHLIGHT_CACHE: Lazy<Mutex<HashMap<u64, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 常用但内置语法无法直接识别的语言名
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("ts", "js"),
+    ("typescript", "js"),
+    ("tsx", "js"),
+    ("jsx", "js"),
+    ("javascript", "js"),
+    ("node", "js"),
+    ("shell", "sh"),
+    ("bash", "sh"),
+    ("zsh", "sh"),
+    ("console", "sh"),
+    ("c++", "cpp"),
+    ("c#", "cs"),
+    ("csharp", "cs"),
+    ("golang", "go"),
+    ("python3", "py"),
+    ("py3", "py"),
+    ("rust", "rs"),
+    ("yml", "yaml"),
+    ("vue", "html"),
+    ("svelte", "html"),
+    ("xhtml", "html"),
+    ("objc", "m"),
+    ("objective-c", "m"),
+    ("markdown", "md"),
+    ("text", "txt"),
+    ("plaintext", "txt"),
+    ("plain", "txt"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighlightConfig {
+    pub enabled: bool,
+    pub line_numbers: bool,
+    pub theme: String,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> Self {
+        HighlightConfig {
+            enabled: true,
+            line_numbers: false,
+            theme: "InspiredGitHub".to_string(),
+        }
+    }
+}
+
+pub fn load_highlight_config(conn: &Connection) -> HighlightConfig {
+    get_setting(conn, SETTING_KEY).unwrap_or_default()
+}
+
+/// 从 info string (如 `rust,ignore`、`python {.class}`) 中取出语言名
+fn language_from_info(info: &str) -> &str {
+    info.split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .unwrap_or("")
+        .trim()
+}
+
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    if language.is_empty() {
+        return None;
+    }
+    let lower = language.to_lowercase();
+    let token = LANGUAGE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == lower)
+        .map(|(_, target)| *target)
+        .unwrap_or(&lower);
+    SYNTAX_SET
+        .find_syntax_by_token(token)
+        .or_else(|| SYNTAX_SET.find_syntax_by_token(language))
+}
+
+fn highlight_to_html(syntax: &SyntaxReference, code: &str) -> Result<String, String> {
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAX_SET, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(generator.finalize())
+}
+
+fn cache_key(language: &str, code: &str, line_numbers: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    language.hash(&mut hasher);
+    code.hash(&mut hasher);
+    line_numbers.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 渲染单个围栏代码块；未知语言输出转义后的纯文本
+pub fn render_code_block(info: &str, code: &str, line_numbers: bool) -> String {
+    let language = language_from_info(info);
+    let key = cache_key(language, code, line_numbers);
+    if let Some(html) = HIGHLIGHT_CACHE.lock().unwrap().get(&key) {
+        return html.clone();
+    }
+
+    let code_html = match find_syntax(language) {
+        Some(syntax) => highlight_to_html(syntax, code).unwrap_or_else(|e| {
+            eprintln!("⚠️ [highlight] 高亮失败 ({}): {}", language, e);
+            escape_html(code)
+        }),
+        None => escape_html(code),
+    };
+
+    let mut pre_class = String::from("code-block hl-code");
+    let mut gutter = String::new();
+    if line_numbers {
+        pre_class.push_str(" line-numbers");
+        let count = code.lines().count().max(1);
+        gutter.push_str("<span class=\"code-line-numbers\">");
+        for n in 1..=count {
+            gutter.push_str(&format!("<span>{}</span>", n));
+        }
+        gutter.push_str("</span>");
+    }
+    let (lang_attr, code_class) = if language.is_empty() {
+        (String::new(), String::new())
+    } else {
+        let escaped = escape_html(language);
+        (
+            format!(" data-lang=\"{}\"", escaped),
+            format!(" class=\"language-{}\"", escaped),
+        )
+    };
+    let html = format!(
+        "<pre class=\"{}\"{}>{}<code{}>{}</code></pre>\n",
+        pre_class, lang_attr, gutter, code_class, code_html
+    );
+
+    let mut cache = HIGHLIGHT_CACHE.lock().unwrap();
+    if cache.len() >= MAX_CACHE_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(key, html.clone());
+    html
+}
+
+/// 将事件流中的围栏代码块替换为高亮后的 HTML (缩进代码块保持默认渲染)
+pub fn highlight_code_blocks<'a>(events: impl Iterator<Item = Event<'a>>, config: &HighlightConfig) -> Vec<Event<'a>> {
+    let mut output = Vec::new();
+    let mut current: Option<(CowStr<'a>, String)> = None;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if config.enabled => {
+                current = Some((info, String::new()));
+            }
+            Event::Text(text) if current.is_some() => {
+                if let Some((_, code)) = current.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) if current.is_some() => {
+                if let Some((info, code)) = current.take() {
+                    let html = render_code_block(&info, &code, config.line_numbers);
+                    output.push(Event::Html(CowStr::from(html)));
+                }
+            }
+            other => output.push(other),
+        }
+    }
+    output
+}
+
+#[command]
+pub async fn get_highlight_config(state: State<'_, AppState>) -> Result<HighlightConfig, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    Ok(load_highlight_config(&conn))
+}
+
+#[command]
+pub async fn set_highlight_config(config: HighlightConfig, state: State<'_, AppState>) -> Result<(), String> {
+    if !THEME_SET.themes.contains_key(&config.theme) {
+        return Err(format!("未知的高亮主题: {}", config.theme));
+    }
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    set_setting(&conn, SETTING_KEY, &config).map_err(|e| e.to_string())
+}
+
+#[command]
+pub async fn list_highlight_themes() -> Result<Vec<String>, String> {
+    Ok(THEME_SET.themes.keys().cloned().collect())
+}
+
+/// 生成主题对应的 CSS (class 与 render_code_block 的输出一致)
+/// theme 省略时使用配置中的主题
+#[command]
+pub async fn get_highlight_css(theme: Option<String>, state: State<'_, AppState>) -> Result<String, String> {
+    let theme_name = match theme {
+        Some(t) => t,
+        None => {
+            let db_pool = state.db_pool.lock().unwrap();
+            let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+            load_highlight_config(&conn).theme
+        }
+    };
+    let theme = THEME_SET
+        .themes
+        .get(&theme_name)
+        .ok_or_else(|| format!("未知的高亮主题: {}", theme_name))?;
+    css_for_theme_with_class_style(theme, CLASS_STYLE).map_err(|e| e.to_string())
+}
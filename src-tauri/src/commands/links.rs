@@ -1,11 +1,15 @@
 // src-tauri/src/commands/links.rs
 
+use crate::commands::fs::save_file;
 use crate::commands::path_utils::to_absolute_path;
+use crate::commands::utils::{escape_like, markdown_options};
 use crate::AppState;
+use pulldown_cmark::{Event, LinkType, Parser, Tag};
 use regex::Regex;
 use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result as RusqliteResult};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::Path;
 use tauri::{command, State};
 
@@ -13,9 +17,92 @@ use tauri::{command, State};
 pub struct LinkItem {
     path: String, // 相对路径
     title: String,
+    link_type: String, // wikilink / embed / markdown
 }
 
-fn parse_wikilinks(content: &str) -> Vec<String> {
+pub const LINK_TYPE_WIKILINK: &str = "wikilink";
+pub const LINK_TYPE_EMBED: &str = "embed";
+pub const LINK_TYPE_MARKDOWN: &str = "markdown";
+
+/// 链接目标中的笔记名部分：去掉 `#标题` 和 `|别名`
+pub fn link_note_name(target: &str) -> &str {
+    target.split(['#', '|']).next().unwrap_or("").trim()
+}
+
+/// 可以被 ![[ ]] 嵌入的附件类型
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+const OTHER_ATTACHMENT_EXTENSIONS: &[&str] = &["pdf", "mp3", "wav", "ogg", "m4a", "mp4", "webm", "mov"];
+
+fn has_extension_in(name: &str, extensions: &[&str]) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+pub fn is_image_target(name: &str) -> bool {
+    has_extension_in(name, IMAGE_EXTENSIONS)
+}
+
+/// 是否为附件 (如 `image.png`)；附件不在 files 表中，不记录链接
+/// 注意笔记名本身可能带点 (如 `2024.01.05`)，因此只识别已知的附件扩展名
+pub fn is_attachment_target(name: &str) -> bool {
+    is_image_target(name) || has_extension_in(name, OTHER_ATTACHMENT_EXTENSIONS)
+}
+
+/// 未解析链接的原因
+pub const UNRESOLVED_MISSING: &str = "missing";
+pub const UNRESOLVED_AMBIGUOUS: &str = "ambiguous";
+
+/// 查找 [[链接]] 可能指向的笔记，返回 (id, 路径)
+/// 依次按 标题精确匹配、别名 (不区分大小写)、文件名 (或以 `/` 分隔的路径后缀) 匹配，
+/// 前一步有结果时不再继续；结果多于一个即为有歧义的链接
+pub fn find_link_targets(conn: &Connection, target: &str) -> RusqliteResult<Vec<(i64, String)>> {
+    let query = |sql: &str, param: &str| -> RusqliteResult<Vec<(i64, String)>> {
+        let mut stmt = conn.prepare_cached(sql)?;
+        let rows = stmt.query_map(params![param], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    };
+    let by_title = query(
+        "SELECT id, path FROM files WHERE title = ?1 AND is_dir = 0 ORDER BY path",
+        target,
+    )?;
+    if !by_title.is_empty() {
+        return Ok(by_title);
+    }
+    let by_alias = query(
+        "SELECT DISTINCT f.id, f.path FROM aliases a INNER JOIN files f ON f.id = a.file_id
+         WHERE a.alias = ?1 AND f.is_dir = 0 ORDER BY f.path",
+        target,
+    )?;
+    if !by_alias.is_empty() {
+        return Ok(by_alias);
+    }
+    let file_name = format!("{}.md", target);
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, path FROM files WHERE (path = ?1 OR path LIKE '%/' || ?2 ESCAPE '\\') AND is_dir = 0 ORDER BY path",
+    )?;
+    let rows = stmt.query_map(params![file_name, escape_like(&file_name)], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+fn insert_unresolved_link(
+    conn: &Connection,
+    source_file_id: i64,
+    target: &str,
+    link_type: &str,
+    reason: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO unresolved_links (source_file_id, target, link_type, reason) VALUES (?1, ?2, ?3, ?4)",
+        params![source_file_id, target, link_type, reason],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("记录未解析链接失败 ('{}'): {}", target, e))
+}
+
+/// 解析 [[链接]] 与 ![[嵌入]]，返回 (笔记名, 链接类型)
+fn parse_wikilinks(content: &str) -> Vec<(String, &'static str)> {
     println!("  🔗 [parse_wikilinks] Received content snippet (debug): {:?}", content.get(..100)); // 保留日志
 
     // ★★★ 新增：移除 Wikilink 前后的转义符 ★★★
@@ -23,17 +110,247 @@ fn parse_wikilinks(content: &str) -> Vec<String> {
     let cleaned_content = content.replace(r"\[\[", "[[").replace(r"\]\]", "]]");
     println!("  🔗 [parse_wikilinks] Cleaned content snippet (debug): {:?}", cleaned_content.get(..100)); // 打印清理后的内容
  
-    let re = Regex::new(r"\[\[([^\]]+)\]\]").unwrap();
+    let re = Regex::new(r"(!?)\[\[([^\]]+)\]\]").unwrap();
  
     // ★★★ 修改：使用清理后的 cleaned_content 进行匹配 ★★★
-    let matches: Vec<String> = re.captures_iter(&cleaned_content) // 使用 cleaned_content
-        .map(|cap| cap[1].trim().to_string())
+    let matches: Vec<(String, &'static str)> = re.captures_iter(&cleaned_content) // 使用 cleaned_content
+        .filter_map(|cap| {
+            let name = link_note_name(&cap[2]);
+            let name = name.strip_suffix(".md").unwrap_or(name);
+            if name.is_empty() || is_attachment_target(name) {
+                return None;
+            }
+            let link_type = if cap[1].is_empty() { LINK_TYPE_WIKILINK } else { LINK_TYPE_EMBED };
+            Some((name.to_string(), link_type))
+        })
         .collect();
 
     println!("  🔗 [parse_wikilinks] Regex matches found: {:?}", matches); // 保留日志
     matches
 }
 
+/// 解码 URL 中的 %XX (如 `%20`)，非法序列原样保留
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// 从链接地址中取出指向笔记的路径部分 (已解码) 和 `#锚点` / `?参数` 后缀
+/// 外部链接 (http:、mailto: 等)、页内锚点和非 .md 目标返回 None
+fn markdown_link_target(dest: &str) -> Option<(String, &str)> {
+    let dest = dest.trim();
+    if dest.is_empty() || dest.starts_with('#') {
+        return None;
+    }
+    let has_scheme = dest
+        .split_once(':')
+        .is_some_and(|(scheme, _)| {
+            !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+        });
+    if has_scheme {
+        return None;
+    }
+    let split_at = dest.find(['#', '?']).unwrap_or(dest.len());
+    let (path, suffix) = dest.split_at(split_at);
+    let path = percent_decode(path);
+    if !path.to_lowercase().ends_with(".md") {
+        return None;
+    }
+    Some((path, suffix))
+}
+
+/// 将链接路径相对于源笔记所在目录解析为工作区相对路径
+/// 以 `/` 开头的路径相对于工作区根目录；越出工作区时返回 None
+pub fn resolve_relative_link(source_path: &str, link_path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = if link_path.starts_with('/') {
+        Vec::new()
+    } else {
+        let mut dir: Vec<&str> = source_path.split('/').collect();
+        dir.pop();
+        dir
+    };
+    for segment in link_path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            other => segments.push(other),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// 计算从 from_note 所在目录指向 to_path 的相对路径
+fn relative_link_between(from_note: &str, to_path: &str) -> String {
+    let mut from_dir: Vec<&str> = from_note.split('/').collect();
+    from_dir.pop();
+    let to: Vec<&str> = to_path.split('/').collect();
+    let common = from_dir
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(to.len() - 1);
+    let mut parts: Vec<&str> = vec![".."; from_dir.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+/// 收集 Markdown 链接 (跳过代码块)，返回 (链接地址, 行内链接在原文中的范围)
+/// 引用式链接 (`[文本][ref]`) 的地址不在链接处，范围为 None
+fn collect_markdown_links(content: &str) -> Vec<(String, Option<Range<usize>>)> {
+    Parser::new_ext(content, markdown_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Link(link_type, dest, _)) => {
+                let range = (link_type == LinkType::Inline).then_some(range);
+                Some((dest.to_string(), range))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// 解析笔记中指向其他笔记的相对 Markdown 链接，返回目标的工作区相对路径
+fn parse_markdown_links(content: &str, source_path: &str) -> Vec<String> {
+    let mut targets: Vec<String> = collect_markdown_links(content)
+        .into_iter()
+        .filter_map(|(dest, _)| markdown_link_target(&dest).map(|(path, _)| path))
+        .filter_map(|path| resolve_relative_link(source_path, &path))
+        .filter(|target| target != source_path)
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// 在行内链接的原文范围内定位地址部分，返回 (地址在原文中的范围, 是否使用 <> 包裹)
+fn locate_link_destination(content: &str, range: Range<usize>, dest: &str) -> Option<(Range<usize>, bool)> {
+    let slice = &content[range.clone()];
+    let open = slice.rfind("](")? + 2;
+    let after = &slice[open..];
+    let start = open + (after.len() - after.trim_start().len());
+    let rest = &slice[start..];
+    let (raw, wrapped) = if let Some(inner) = rest.strip_prefix('<') {
+        (&inner[..inner.find('>')?], true)
+    } else {
+        (&rest[..rest.find(|c: char| c.is_whitespace() || c == ')')?], false)
+    };
+    if raw != dest {
+        return None;
+    }
+    let raw_start = range.start + start + usize::from(wrapped);
+    Some((raw_start..raw_start + raw.len(), wrapped))
+}
+
+/// 文件移动/重命名后重写笔记中的相对 Markdown 链接
+/// old_source / new_source 为该笔记移动前后的路径，moves 为所有被移动文件的 旧路径 -> 新路径
+/// 内容无需修改时返回 None
+pub fn rewrite_markdown_links(
+    content: &str,
+    old_source: &str,
+    new_source: &str,
+    moves: &HashMap<String, String>,
+) -> Option<String> {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for (dest, range) in collect_markdown_links(content) {
+        let Some(range) = range else { continue };
+        let Some((path, suffix)) = markdown_link_target(&dest) else { continue };
+        let Some(old_target) = resolve_relative_link(old_source, &path) else { continue };
+        let new_target = moves.get(&old_target).cloned().unwrap_or(old_target);
+
+        let mut new_path = relative_link_between(new_source, &new_target);
+        if path.starts_with("./") && !new_path.starts_with("../") {
+            new_path.insert_str(0, "./");
+        }
+        if path.starts_with('/') {
+            new_path = format!("/{}", new_target);
+        }
+        if new_path == path {
+            continue;
+        }
+        let Some((dest_range, wrapped)) = locate_link_destination(content, range, &dest) else { continue };
+        // 原链接使用了 %20 编码或新路径含空格但未用 <> 包裹时，对空格编码
+        if !wrapped && (dest.contains('%') || new_path.contains(' ')) {
+            new_path = new_path.replace(' ', "%20");
+        }
+        edits.push((dest_range, format!("{}{}", new_path, suffix)));
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+    let mut result = content.to_string();
+    for (range, replacement) in edits.into_iter().rev() {
+        result.replace_range(range, &replacement);
+    }
+    Some(result)
+}
+
+/// 移动/重命名完成后，更新所有受影响笔记中的相对 Markdown 链接：
+/// 指向被移动文件的笔记，以及被移动的笔记本身 (其所在目录已变化)
+/// 修改通过 save_file 写回 (会同时更新链接表、历史和索引)，返回被修改的笔记数
+pub async fn rewrite_links_after_move(
+    root_path: &str,
+    moves: &[(String, String)],
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let move_map: HashMap<String, String> = moves.iter().cloned().collect();
+    let reverse_map: HashMap<&str, &str> = moves.iter().map(|(old, new)| (new.as_str(), old.as_str())).collect();
+
+    let mut candidates: Vec<String> = moves.iter().map(|(_, new)| new.clone()).collect();
+    {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT s.path FROM links l
+                 INNER JOIN files s ON s.id = l.source_file_id
+                 INNER JOIN files t ON t.id = l.target_file_id
+                 WHERE l.link_type = ?1 AND t.path = ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        for (_, new_path) in moves {
+            let rows = stmt
+                .query_map(params![LINK_TYPE_MARKDOWN, new_path], |row| row.get::<_, String>(0))
+                .map_err(|e| e.to_string())?;
+            for row in rows {
+                candidates.push(row.map_err(|e| e.to_string())?);
+            }
+        }
+    }
+    candidates.sort();
+    candidates.dedup();
+
+    let mut rewritten = 0;
+    for current_path in candidates {
+        let old_source = reverse_map.get(current_path.as_str()).copied().unwrap_or(&current_path);
+        let absolute_path = to_absolute_path(Path::new(root_path), Path::new(&current_path));
+        let Ok(content) = std::fs::read_to_string(&absolute_path) else { continue };
+        if let Some(new_content) = rewrite_markdown_links(&content, old_source, &current_path, &move_map) {
+            println!("  🔗 [rewrite_links] 更新 Markdown 链接: {}", current_path);
+            save_file(root_path.to_string(), current_path.clone(), new_content, state.clone()).await?;
+            rewritten += 1;
+        }
+    }
+    Ok(rewritten)
+}
+
 // file_path 应该是相对路径
 // file_path 应该是相对路径
 pub fn update_links_for_file(
@@ -81,76 +398,72 @@ pub fn update_links_for_file(
         params![source_file_id],
     )
     .map_err(|e| format!("删除旧链接失败: {}", e))?; // ★★★ 修改错误信息 ★★★
+    tx.execute(
+        "DELETE FROM unresolved_links WHERE source_file_id = ?1",
+        params![source_file_id],
+    )
+    .map_err(|e| format!("删除旧的未解析链接失败: {}", e))?;
     // println!("  🔗 [update_links] 已删除旧链接"); // 可选日志
  
     let linked_targets = parse_wikilinks(&content);
     println!("  🔗 [update_links] 解析到 {} 个链接目标: {:?}", linked_targets.len(), linked_targets); // 新增日志
  
-    if !linked_targets.is_empty() {
-        let mut title_stmt = tx.prepare("SELECT id FROM files WHERE title = ?1").map_err(|e| format!("准备 title 查询失败: {}", e))?; // ★★★ 修改错误信息 ★★★
-        let mut path_stmt = tx.prepare("SELECT id FROM files WHERE path LIKE ('%' || ?1)").map_err(|e| format!("准备 path 查询失败: {}", e))?; // ★★★ 修改错误信息 ★★★
- 
-        for target in linked_targets {
-            println!("    🔗 [update_links] 正在处理目标: '{}'", target); // 新增日志
-            let mut target_ids: Vec<i64> = Vec::new();
-
-            // 尝试按 title 查询
-            match title_stmt.query_map(params![&target], |row| row.get(0)) {
-                Ok(rows) => {
-                    for id_result in rows {
-                        match id_result {
-                            Ok(id_val) => target_ids.push(id_val),
-                            Err(e) => eprintln!("      🔗 [update_links] 读取 title 查询结果失败: {}", e), // 记录行读取错误
-                        }
-                    }
-                    println!("      🔗 [update_links] 按 title 查询到 {} 个 ID: {:?}", target_ids.len(), target_ids); // 新增日志
-                }
-                Err(e) => {
-                    eprintln!("      🔗 [update_links] 按 title 查询时出错: {}", e); // 记录查询错误
-                }
-            }
- 
- 
-            // 如果按 title 找不到，尝试按 path 查询
-            if target_ids.is_empty() {
-                println!("      🔗 [update_links] 按 title 未找到，尝试按 path..."); // 新增日志
-                let path_pattern = format!("{}.md", &target);
-                println!("        🔗 [update_links] Path pattern: '%{}'", path_pattern); // 新增日志
-                match path_stmt.query_map(params![&path_pattern], |row| row.get(0)) {
-                   Ok(rows) => {
-                        for id_result in rows {
-                            match id_result {
-                                Ok(id_val) => target_ids.push(id_val),
-                                Err(e) => eprintln!("        🔗 [update_links] 读取 path 查询结果失败: {}", e), // 记录行读取错误
-                            }
-                        }
-                        println!("      🔗 [update_links] 按 path 查询到 {} 个 ID: {:?}", target_ids.len(), target_ids); // 新增日志
-                    }
-                    Err(e) => {
-                        eprintln!("      🔗 [update_links] 按 path 查询时出错: {}", e); // 记录查询错误
-                    }
-                }
-            }
- 
-            // ★★★ 只有当精确找到一个目标时才插入 ★★★
-            if target_ids.len() == 1 {
-                let target_file_id = target_ids[0];
+    for (target, link_type) in linked_targets {
+        println!("    🔗 [update_links] 正在处理目标: '{}' ({})", target, link_type); // 新增日志
+        let target_ids: Vec<i64> = find_link_targets(&tx, &target)
+            .map_err(|e| format!("查询链接目标失败 ('{}'): {}", target, e))?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+
+        // ★★★ 只有当精确找到一个目标时才插入，其余记录为未解析链接 ★★★
+        match target_ids.as_slice() {
+            [target_file_id] => {
                 println!("      🔗 [update_links] 找到唯一目标 ID: {}, 准备插入链接 {} -> {}", target_file_id, source_file_id, target_file_id); // 新增日志
                 tx.execute(
-                    "INSERT OR IGNORE INTO links (source_file_id, target_file_id) VALUES (?1, ?2)",
-                    params![source_file_id, target_file_id],
+                    "INSERT OR IGNORE INTO links (source_file_id, target_file_id, link_type) VALUES (?1, ?2, ?3)",
+                    params![source_file_id, target_file_id, link_type],
                 )
                 .map_err(|e| format!("插入链接失败 ({} -> {}): {}", source_file_id, target_file_id, e))?; // ★★★ 修改错误信息 ★★★
-            } else {
-                // ★★★ 记录为什么没有插入 ★★★
-                if target_ids.is_empty() {
-                    println!("      🔗 [update_links] 未找到目标 '{}' 的 ID，跳过插入", target);
-                } else {
-                    println!("      🔗 [update_links] 找到多个目标 '{}' 的 ID ({:?})，跳过插入", target, target_ids);
-                }
+            }
+            [] => {
+                println!("      🔗 [update_links] 未找到目标 '{}' 的 ID，记录为断开的链接", target);
+                insert_unresolved_link(&tx, source_file_id, &target, link_type, UNRESOLVED_MISSING)?;
+            }
+            _ => {
+                println!("      🔗 [update_links] 找到多个目标 '{}' 的 ID ({:?})，记录为有歧义的链接", target, target_ids);
+                insert_unresolved_link(&tx, source_file_id, &target, link_type, UNRESOLVED_AMBIGUOUS)?;
             }
         }
     }
+
+    // 相对 Markdown 链接：相对于当前文件所在目录解析，按路径精确匹配
+    let markdown_targets = parse_markdown_links(&content, relative_path);
+    println!("  🔗 [update_links] 解析到 {} 个 Markdown 链接: {:?}", markdown_targets.len(), markdown_targets);
+    for target_path in markdown_targets {
+        let target_file_id: Option<i64> = tx
+            .query_row(
+                "SELECT id FROM files WHERE path = ?1 AND is_dir = 0",
+                params![target_path],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("查询 Markdown 链接目标失败: {}", e))?;
+        match target_file_id {
+            Some(target_file_id) => {
+                tx.execute(
+                    "INSERT OR IGNORE INTO links (source_file_id, target_file_id, link_type) VALUES (?1, ?2, ?3)",
+                    params![source_file_id, target_file_id, LINK_TYPE_MARKDOWN],
+                )
+                .map_err(|e| format!("插入链接失败 ({} -> {}): {}", source_file_id, target_file_id, e))?;
+            }
+            None => {
+                println!("      🔗 [update_links] Markdown 链接目标不存在: '{}'，记录为断开的链接", target_path);
+                insert_unresolved_link(&tx, source_file_id, &target_path, LINK_TYPE_MARKDOWN, UNRESOLVED_MISSING)?;
+            }
+        }
+    }
+
     tx.commit().map_err(|e| format!("提交事务失败: {}", e))?; // ★★★ 修改错误信息 ★★★
     println!("  🔗 [update_links] 事务提交成功"); // 新增日志
     Ok(())
@@ -160,17 +473,123 @@ pub fn update_links_for_file(
 pub async fn get_backlinks(relative_path: String, state: State<'_, AppState>) -> Result<Vec<LinkItem>, String> {
     let db_pool = state.db_pool.lock().unwrap();
     let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare("SELECT f.path, f.title FROM files f INNER JOIN links l ON f.id = l.source_file_id WHERE l.target_file_id = (SELECT id FROM files WHERE path = ?1) ORDER BY f.title").map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare("SELECT f.path, f.title, l.link_type FROM files f INNER JOIN links l ON f.id = l.source_file_id WHERE l.target_file_id = (SELECT id FROM files WHERE path = ?1) ORDER BY l.link_type DESC, f.title").map_err(|e| e.to_string())?;
     let link_iter = stmt.query_map(params![relative_path], |row| -> RusqliteResult<LinkItem> {
         Ok(LinkItem {
             path: row.get(0)?,
             title: row.get::<_, Option<String>>(1)?.unwrap_or_else(|| "无标题".to_string()),
+            link_type: row.get(2)?,
         })
     }).map_err(|e| e.to_string())?;
     let links: Vec<LinkItem> = link_iter.filter_map(Result::ok).collect();
     Ok(links)
 }
 
+/// 断开或有歧义的链接
+#[derive(Debug, Serialize, Clone)]
+pub struct UnresolvedLink {
+    pub source_path: String,
+    pub source_title: String,
+    pub target: String,
+    pub link_type: String,
+    pub candidates: Vec<String>, // 有歧义时可能指向的笔记路径
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OrphanNote {
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LinkHealthReport {
+    pub broken: Vec<UnresolvedLink>,
+    pub ambiguous: Vec<UnresolvedLink>,
+    pub orphans: Vec<OrphanNote>,
+}
+
+/// 链接检查报告：断开的链接、有歧义的链接 (附候选目标) 和孤立笔记 (没有任何入链和出链)
+/// 未解析链接在保存时记录，报告时按当前的笔记重新解析，之后新建的笔记也会被考虑在内
+#[command]
+pub async fn get_link_health(state: State<'_, AppState>) -> Result<LinkHealthReport, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.path, f.title, u.target, u.link_type FROM unresolved_links u
+             INNER JOIN files f ON f.id = u.source_file_id
+             ORDER BY f.path, u.target",
+        )
+        .map_err(|e| e.to_string())?;
+    let unresolved: Vec<(String, Option<String>, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<RusqliteResult<_>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut broken = Vec::new();
+    let mut ambiguous = Vec::new();
+    for (source_path, source_title, target, link_type) in unresolved {
+        let candidates: Vec<String> = if link_type == LINK_TYPE_MARKDOWN {
+            conn.query_row(
+                "SELECT path FROM files WHERE path = ?1 AND is_dir = 0",
+                params![target],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect()
+        } else {
+            find_link_targets(&conn, &target)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|(_, path)| path)
+                .collect()
+        };
+        let item = UnresolvedLink {
+            source_title: source_title.unwrap_or_else(|| "无标题".to_string()),
+            source_path,
+            target,
+            link_type,
+            candidates,
+        };
+        match item.candidates.len() {
+            0 => broken.push(item),
+            1 => {} // 目标笔记已创建，下次保存源笔记时会写入 links 表
+            _ => ambiguous.push(item),
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, title FROM files f
+             WHERE is_dir = 0 AND path LIKE '%.md'
+               AND NOT EXISTS (SELECT 1 FROM links WHERE source_file_id = f.id OR target_file_id = f.id)
+             ORDER BY path",
+        )
+        .map_err(|e| e.to_string())?;
+    let orphans: Vec<OrphanNote> = stmt
+        .query_map([], |row| {
+            Ok(OrphanNote {
+                path: row.get(0)?,
+                title: row.get::<_, Option<String>>(1)?.unwrap_or_else(|| "无标题".to_string()),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .collect();
+
+    println!(
+        "🔗 [link_health] 断开 {} 个，有歧义 {} 个，孤立笔记 {} 个",
+        broken.len(),
+        ambiguous.len(),
+        orphans.len()
+    );
+    Ok(LinkHealthReport { broken, ambiguous, orphans })
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct DebugLink {
     source_id: i64,
@@ -207,7 +626,7 @@ pub struct GraphData {
 pub async fn get_graph_data(state: State<'_, AppState>) -> Result<GraphData, String> {
     let db_pool = state.db_pool.lock().unwrap();
     let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
-    let mut edges_stmt = conn.prepare("SELECT source_file_id, target_file_id FROM links").map_err(|e| e.to_string())?;
+    let mut edges_stmt = conn.prepare("SELECT DISTINCT source_file_id, target_file_id FROM links").map_err(|e| e.to_string())?;
     let edges_iter = edges_stmt.query_map([], |row| Ok(GraphEdge { from: row.get(0)?, to: row.get(1)? })).map_err(|e| e.to_string())?;
     let mut edges = Vec::new();
     let mut connected_node_ids = HashSet::<i64>::new();
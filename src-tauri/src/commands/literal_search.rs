@@ -0,0 +1,287 @@
+// src-tauri/src/commands/literal_search.rs
+// 字面文本 / 正则搜索：分词搜索找不到的内容 (如 TODO(alice)、IP 地址片段) 直接扫描笔记原文
+// 可用索引预先筛选候选笔记，多线程扫描，每个有匹配的笔记通过事件实时推送给前端
+
+use crate::commands::path_utils::to_absolute_path;
+use crate::{search_core, AppState};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+use tauri::{command, AppHandle, Emitter, State};
+
+/// 单个笔记最多返回的匹配数
+const MAX_MATCHES_PER_FILE: usize = 200;
+/// 一次搜索最多返回的匹配数，超过后停止扫描
+const MAX_TOTAL_MATCHES: usize = 5000;
+/// 超过该大小的文件不扫描
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// 匹配行前后附带的上下文行数
+const CONTEXT_LINES: usize = 1;
+/// 匹配行过长时，匹配处前后保留的字符数
+const LINE_CONTEXT_CHARS: usize = 80;
+/// 正则编译后的大小上限，避免病态正则占用过多内存
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// 当前搜索的编号；开始新的搜索后，旧搜索的扫描线程会尽快停止
+static ACTIVE_SEARCH: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiteralMatch {
+    pub line: usize,            // 从 1 开始
+    pub column: usize,          // 从 1 开始，UTF-16 单位，与编辑器一致
+    pub byte_start: usize,      // 在笔记内容中的字节位置
+    pub byte_end: usize,
+    pub line_text: String,      // 匹配所在行 (过长时截取匹配附近的一段)
+    pub match_start: usize,     // 匹配在 line_text 中的位置 [start, end)，UTF-16 单位
+    pub match_end: usize,
+    pub before: Vec<String>,    // 前面的上下文行
+    pub after: Vec<String>,     // 后面的上下文行
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiteralFileMatches {
+    pub search_id: u64,
+    pub path: String,
+    pub title: String,
+    pub matches: Vec<LiteralMatch>,
+    pub truncated: bool, // 该笔记的匹配超过上限
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiteralSearchSummary {
+    pub search_id: u64,
+    pub files_scanned: usize,
+    pub files_matched: usize,
+    pub total_matches: usize,
+    pub truncated: bool,  // 达到匹配总数上限，或被新的搜索取消
+    pub prefiltered: bool, // 是否使用了索引预筛选
+    pub elapsed_ms: u64,
+}
+
+/// 构造搜索用的正则：字面文本会先转义
+pub fn build_literal_regex(pattern: &str, is_regex: bool, case_sensitive: bool) -> Result<Regex, String> {
+    if pattern.is_empty() {
+        return Err("搜索内容为空".to_string());
+    }
+    let source = if is_regex { pattern.to_string() } else { regex::escape(pattern) };
+    RegexBuilder::new(&source)
+        .case_insensitive(!case_sensitive)
+        .multi_line(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("正则表达式无效: {}", e))
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// 截取过长的行：保留匹配处前后各 LINE_CONTEXT_CHARS 个字符
+/// 返回 (行文本, 匹配在行文本中的字节范围)
+fn clip_line(line: &str, start: usize, end: usize) -> (String, usize, usize) {
+    let before = &line[..start];
+    let clip_start = before
+        .char_indices()
+        .rev()
+        .nth(LINE_CONTEXT_CHARS.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let after = &line[end..];
+    let clip_end = after
+        .char_indices()
+        .nth(LINE_CONTEXT_CHARS)
+        .map(|(i, _)| end + i)
+        .unwrap_or(line.len());
+    let prefix = if clip_start > 0 { "…" } else { "" };
+    let suffix = if clip_end < line.len() { "…" } else { "" };
+    let text = format!("{}{}{}", prefix, &line[clip_start..clip_end], suffix);
+    let match_start = prefix.len() + start - clip_start;
+    (text, match_start, match_start + end - start)
+}
+
+/// 在内容中查找所有匹配，返回匹配及是否超过单个笔记的上限
+pub fn find_literal_matches(content: &str, regex: &Regex, limit: usize) -> (Vec<LiteralMatch>, bool) {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_text = |index: usize| -> &str {
+        let start = line_starts[index];
+        let end = line_starts.get(index + 1).map(|e| e - 1).unwrap_or(content.len());
+        content[start..end].trim_end_matches('\r')
+    };
+
+    let mut matches = Vec::new();
+    for m in regex.find_iter(content) {
+        if m.start() == m.end() {
+            continue; // 忽略空匹配 (如 ^、\b)
+        }
+        if matches.len() >= limit {
+            return (matches, true);
+        }
+        let line_index = line_starts.partition_point(|&s| s <= m.start()) - 1;
+        let line_start = line_starts[line_index];
+        let line = line_text(line_index);
+        // 跨行匹配只在第一行中标出
+        let start_in_line = (m.start() - line_start).min(line.len());
+        let end_in_line = (m.end() - line_start).min(line.len());
+        let (text, match_start, match_end) = clip_line(line, start_in_line, end_in_line.max(start_in_line));
+
+        let before = (line_index.saturating_sub(CONTEXT_LINES)..line_index)
+            .map(|i| line_text(i).to_string())
+            .collect();
+        let after = (line_index + 1..(line_index + 1 + CONTEXT_LINES).min(line_starts.len()))
+            .map(|i| line_text(i).to_string())
+            .collect();
+        matches.push(LiteralMatch {
+            line: line_index + 1,
+            column: utf16_len(&line[..start_in_line]) + 1,
+            byte_start: m.start(),
+            byte_end: m.end(),
+            match_start: utf16_len(&text[..match_start]),
+            match_end: utf16_len(&text[..match_end]),
+            line_text: text,
+            before,
+            after,
+        });
+    }
+    (matches, false)
+}
+
+struct ScanCounts {
+    files_scanned: usize,
+    files_matched: usize,
+    total_matches: usize,
+    stopped: bool,
+}
+
+/// 多线程扫描笔记 (路径, 标题)，每个有匹配的笔记发送一次 literal-search-result 事件
+/// 达到匹配总数上限或开始了新的搜索时提前停止
+fn scan_notes(search_id: u64, notes: &[(String, String)], root_path: &str, regex: &Regex, app: &AppHandle) -> ScanCounts {
+    let next = AtomicUsize::new(0);
+    let scanned = AtomicUsize::new(0);
+    let matched_files = AtomicUsize::new(0);
+    let total_matches = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(notes.len().max(1));
+    let root = Path::new(root_path);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                if ACTIVE_SEARCH.load(Ordering::SeqCst) != search_id || stopped.load(Ordering::SeqCst) {
+                    stopped.store(true, Ordering::SeqCst);
+                    break;
+                }
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some((path, title)) = notes.get(i) else { break };
+                let absolute_path = to_absolute_path(root, Path::new(path));
+                if std::fs::metadata(&absolute_path).map(|m| m.len() > MAX_FILE_BYTES).unwrap_or(true) {
+                    continue;
+                }
+                let Ok(content) = std::fs::read_to_string(&absolute_path) else { continue };
+                scanned.fetch_add(1, Ordering::SeqCst);
+
+                let (matches, truncated) = find_literal_matches(&content, regex, MAX_MATCHES_PER_FILE);
+                if matches.is_empty() {
+                    continue;
+                }
+                let count = matches.len();
+                if total_matches.fetch_add(count, Ordering::SeqCst) + count >= MAX_TOTAL_MATCHES {
+                    stopped.store(true, Ordering::SeqCst);
+                }
+                matched_files.fetch_add(1, Ordering::SeqCst);
+                let title = if title.is_empty() { path.clone() } else { title.clone() };
+                let _ = app.emit(
+                    "literal-search-result",
+                    LiteralFileMatches { search_id, path: path.clone(), title, matches, truncated },
+                );
+            });
+        }
+    });
+
+    ScanCounts {
+        files_scanned: scanned.into_inner(),
+        files_matched: matched_files.into_inner(),
+        total_matches: total_matches.into_inner(),
+        stopped: stopped.into_inner(),
+    }
+}
+
+/// 在工作区所有笔记中搜索字面文本或正则表达式
+/// 扫描过程中对每个有匹配的笔记发送 literal-search-result 事件，开始和结束时分别发送
+/// literal-search-started / literal-search-finished；返回值与结束事件相同
+#[command]
+pub async fn search_literal(
+    pattern: String,
+    is_regex: bool,
+    case_sensitive: bool,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<LiteralSearchSummary, String> {
+    let started = Instant::now();
+    let regex = build_literal_regex(&pattern, is_regex, case_sensitive)?;
+    let root_path = state.current_path.lock().unwrap().clone().ok_or("工作区未打开")?;
+    let search_id = ACTIVE_SEARCH.fetch_add(1, Ordering::SeqCst) + 1;
+
+    // 1. 候选笔记：字面文本先用索引筛选，未索引的笔记始终扫描
+    let (notes, unindexed): (Vec<(String, String)>, HashSet<String>) = {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT path, COALESCE(title, ''), COALESCE(indexed, 0) FROM files WHERE is_dir = 0 ORDER BY path")
+            .map_err(|e| e.to_string())?;
+        let rows: Vec<(String, String, i64)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        let unindexed = rows.iter().filter(|(_, _, indexed)| *indexed == 0).map(|(p, _, _)| p.clone()).collect();
+        (rows.into_iter().map(|(path, title, _)| (path, title)).collect(), unindexed)
+    };
+    let index = state.search_index.lock().unwrap().as_ref().cloned();
+    let candidates: Option<HashSet<String>> = match (&index, is_regex) {
+        (Some(index), false) => search_core::literal_candidates(index, &pattern)
+            .map_err(|e| e.to_string())?
+            .map(|paths| paths.into_iter().collect()),
+        _ => None,
+    };
+    let prefiltered = candidates.is_some();
+    let notes: Vec<(String, String)> = match &candidates {
+        Some(candidates) => notes
+            .into_iter()
+            .filter(|(path, _)| candidates.contains(path) || unindexed.contains(path))
+            .collect(),
+        None => notes,
+    };
+
+    let _ = app.emit(
+        "literal-search-started",
+        serde_json::json!({ "search_id": search_id, "pattern": pattern, "files": notes.len() }),
+    );
+    println!("🔎 [literal] #{} '{}' 扫描 {} 个笔记 (索引预筛选: {})", search_id, pattern, notes.len(), prefiltered);
+
+    // 2. 在阻塞线程池中多线程扫描，不占用异步运行时，新的搜索可以随时开始并取消本次搜索
+    let scan_app = app.clone();
+    let counts = tauri::async_runtime::spawn_blocking(move || scan_notes(search_id, &notes, &root_path, &regex, &scan_app))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let summary = LiteralSearchSummary {
+        search_id,
+        files_scanned: counts.files_scanned,
+        files_matched: counts.files_matched,
+        total_matches: counts.total_matches,
+        truncated: counts.stopped,
+        prefiltered,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    let _ = app.emit("literal-search-finished", summary.clone());
+    println!(
+        "🔎 [literal] #{} 完成: {} 个笔记中 {} 处匹配，用时 {} ms",
+        search_id, summary.files_matched, summary.total_matches, summary.elapsed_ms
+    );
+    Ok(summary)
+}
@@ -0,0 +1,268 @@
+// src-tauri/src/commands/mentions.rs
+// 未链接提及：正文中出现了目标笔记的标题 (或别名)，但没有链接到它
+
+use crate::commands::fs::save_file;
+use crate::commands::path_utils::to_absolute_path;
+use crate::commands::properties::split_frontmatter;
+use crate::commands::utils::{escape_html, markdown_options, resolve_wikilink};
+use crate::{search_core, AppState};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag};
+use regex::Regex;
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+use tauri::{command, State};
+
+/// 从索引中取出的候选笔记上限
+const MAX_CANDIDATE_NOTES: usize = 200;
+
+/// 片段中匹配处前后保留的字符数
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+static WIKILINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"!?\[\[[^\]]+\]\]").unwrap());
+
+/// 笔记中的一处提及
+#[derive(Debug, Clone, Serialize)]
+pub struct MentionMatch {
+    pub text: String,       // 原文中匹配到的文本 (保留原大小写)
+    pub line: usize,        // 从 1 开始
+    pub pos: usize,         // UTF-16 偏移，与编辑器的位置一致
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub snippet: String,    // 所在行的片段 (HTML)，匹配处用 <mark> 标出
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnlinkedMention {
+    pub path: String,
+    pub title: String,
+    pub mentions: Vec<MentionMatch>,
+}
+
+/// 不计为提及的区域：frontmatter、代码、已有的链接和 [[ ]]
+fn excluded_ranges(content: &str) -> Vec<Range<usize>> {
+    let body_start = split_frontmatter(content).map(|(_, start)| start).unwrap_or(0);
+    let body = &content[body_start..];
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    if body_start > 0 {
+        ranges.push(0..body_start);
+    }
+    let mut open: Vec<usize> = Vec::new();
+    for (event, range) in Parser::new_ext(body, markdown_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::Link(..)) | Event::Start(Tag::Image(..)) => {
+                open.push(range.start);
+            }
+            Event::End(Tag::CodeBlock(_)) | Event::End(Tag::Link(..)) | Event::End(Tag::Image(..)) => {
+                if let Some(start) = open.pop() {
+                    ranges.push(body_start + start..body_start + range.end);
+                }
+            }
+            Event::Code(_) | Event::Html(_) => ranges.push(body_start + range.start..body_start + range.end),
+            _ => {}
+        }
+    }
+    ranges.extend(WIKILINK_RE.find_iter(content).map(|m| m.range()));
+    ranges
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// 截取匹配所在行的一段，匹配处加 <mark>
+fn mention_snippet(content: &str, range: &Range<usize>) -> String {
+    let line_start = content[..range.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = content[range.end..].find('\n').map(|i| range.end + i).unwrap_or(content.len());
+
+    let before = &content[line_start..range.start];
+    let before_start = before
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let after = &content[range.end..line_end];
+    let after_end = after
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map(|(i, _)| i)
+        .unwrap_or(after.len());
+
+    format!(
+        "{}{}<mark>{}</mark>{}{}",
+        if before_start > 0 { "…" } else { "" },
+        escape_html(before[before_start..].trim_start()),
+        escape_html(&content[range.clone()]),
+        escape_html(after[..after_end].trim_end()),
+        if after_end < after.len() { "…" } else { "" }
+    )
+}
+
+/// 在正文中查找 terms 的出现位置 (忽略大小写)，跳过代码、链接和 frontmatter
+/// 英文词语要求完整单词匹配；多个词语重叠时保留较长的匹配
+pub fn find_mentions(content: &str, terms: &[String]) -> Vec<MentionMatch> {
+    let mut terms: Vec<&str> = terms.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+    terms.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    let excluded = excluded_ranges(content);
+    let bytes = content.as_bytes();
+    let mut found: Vec<Range<usize>> = Vec::new();
+    for term in terms {
+        let Ok(re) = Regex::new(&format!("(?i){}", regex::escape(term))) else { continue };
+        for m in re.find_iter(content) {
+            let range = m.range();
+            let starts_word = bytes[range.start].is_ascii_alphanumeric();
+            let ends_word = bytes[range.end - 1].is_ascii_alphanumeric();
+            if starts_word && range.start > 0 && is_word_byte(bytes[range.start - 1]) {
+                continue;
+            }
+            if ends_word && range.end < bytes.len() && is_word_byte(bytes[range.end]) {
+                continue;
+            }
+            let overlaps = |r: &Range<usize>| r.start < range.end && range.start < r.end;
+            if excluded.iter().any(overlaps) || found.iter().any(overlaps) {
+                continue;
+            }
+            found.push(range);
+        }
+    }
+    found.sort_by_key(|r| r.start);
+
+    found
+        .into_iter()
+        .map(|range| MentionMatch {
+            text: content[range.clone()].to_string(),
+            line: content[..range.start].matches('\n').count() + 1,
+            pos: content[..range.start].encode_utf16().count(),
+            snippet: mention_snippet(content, &range),
+            byte_start: range.start,
+            byte_end: range.end,
+        })
+        .collect()
+}
+
+/// 目标笔记可被提及的名称：标题、文件名与别名
+fn mention_terms(relative_path: &str, title: Option<String>, aliases: Vec<String>) -> Vec<String> {
+    let stem = Path::new(relative_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(str::to_string);
+    let mut terms: Vec<String> = Vec::new();
+    for term in title.into_iter().chain(stem).chain(aliases) {
+        if !term.trim().is_empty() && !terms.iter().any(|t| t.eq_ignore_ascii_case(&term)) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// 获取提及了目标笔记但未链接到它的笔记
+#[command]
+pub async fn get_unlinked_mentions(relative_path: String, state: State<'_, AppState>) -> Result<Vec<UnlinkedMention>, String> {
+    let (terms, linked_sources) = {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        let (target_id, title): (i64, Option<String>) = conn
+            .query_row(
+                "SELECT id, title FROM files WHERE path = ?1",
+                params![relative_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("笔记不存在: {}", relative_path))?;
+
+        let mut stmt = conn
+            .prepare("SELECT f.path FROM links l INNER JOIN files f ON f.id = l.source_file_id WHERE l.target_file_id = ?1")
+            .map_err(|e| e.to_string())?;
+        let linked_sources: HashSet<String> = stmt
+            .query_map(params![target_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .collect();
+        let mut stmt = conn
+            .prepare("SELECT alias FROM aliases WHERE file_id = ?1 ORDER BY alias")
+            .map_err(|e| e.to_string())?;
+        let aliases: Vec<String> = stmt
+            .query_map(params![target_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .collect();
+        (mention_terms(&relative_path, title, aliases), linked_sources)
+    };
+
+    let index = state
+        .search_index
+        .lock()
+        .unwrap()
+        .as_ref()
+        .cloned()
+        .ok_or("索引未初始化")?;
+    let candidates =
+        search_core::find_phrase_matches(&index, &terms, MAX_CANDIDATE_NOTES).map_err(|e| e.to_string())?;
+
+    let mut results: Vec<UnlinkedMention> = candidates
+        .into_iter()
+        .filter(|(path, _, _)| *path != relative_path && !linked_sources.contains(path))
+        .filter_map(|(path, title, content)| {
+            let mentions = find_mentions(&content, &terms);
+            (!mentions.is_empty()).then_some(UnlinkedMention { path, title, mentions })
+        })
+        .collect();
+    results.sort_by(|a, b| b.mentions.len().cmp(&a.mentions.len()).then_with(|| a.title.cmp(&b.title)));
+    println!("🔗 [mentions] {} 的未链接提及: {} 个笔记", relative_path, results.len());
+    Ok(results)
+}
+
+/// 将一处未链接提及替换为指向目标笔记的 [[链接]]，返回修改后的内容
+/// byte_start 与 text 来自 get_unlinked_mentions，内容已变化时拒绝修改
+#[command]
+pub async fn convert_mention_to_link(
+    root_path: String,
+    source_path: String,
+    target_path: String,
+    byte_start: usize,
+    text: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let absolute_path = to_absolute_path(Path::new(&root_path), Path::new(&source_path));
+    let content = std::fs::read_to_string(&absolute_path).map_err(|e| format!("读取笔记失败: {}", e))?;
+    let byte_end = byte_start + text.len();
+    if content.get(byte_start..byte_end) != Some(text.as_str()) {
+        return Err("笔记内容已变化，请刷新未链接提及".to_string());
+    }
+
+    // 优先用标题作为链接名；标题解析不到目标 (如重名) 时改用不含扩展名的路径
+    let link_name = {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        let title: Option<String> = conn
+            .query_row("SELECT title FROM files WHERE path = ?1", params![target_path], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten();
+        let by_path = target_path.strip_suffix(".md").unwrap_or(&target_path).to_string();
+        match title {
+            Some(title) if resolve_wikilink(&conn, &title).as_deref() == Some(target_path.as_str()) => title,
+            _ => by_path,
+        }
+    };
+
+    let link = if link_name == text {
+        format!("[[{}]]", link_name)
+    } else {
+        format!("[[{}|{}]]", link_name, text)
+    };
+    let mut new_content = content;
+    new_content.replace_range(byte_start..byte_end, &link);
+    println!("🔗 [mentions] {} 中的 '{}' 已转换为 {}", source_path, text, link);
+
+    save_file(root_path, source_path, new_content.clone(), state).await?;
+    Ok(new_content)
+}
@@ -9,4 +9,20 @@ pub mod pins;
 pub mod links; 
 pub mod path_utils; // [新增]
 pub mod workspace;
-pub mod sync; // 
\ No newline at end of file
+pub mod sync; // 
+pub mod periodic;
+pub mod properties;
+pub mod query;
+pub mod tasks;
+pub mod outline;
+pub mod sanitize;
+pub mod highlight;
+pub mod special_blocks;
+pub mod embeds;
+pub mod mentions;
+pub mod graph;
+pub mod related;
+pub mod quick_open;
+pub mod literal_search;
+pub mod replace;
+pub mod saved_searches;
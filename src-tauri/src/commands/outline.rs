@@ -0,0 +1,203 @@
+// src-tauri/src/commands/outline.rs
+// 标题大纲 (heading tree) 与目录 ([TOC]) 生成
+
+use crate::commands::properties::split_frontmatter;
+use crate::commands::utils::{escape_html, markdown_options};
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::command;
+
+/// 扁平的标题信息 (按文档顺序)
+#[derive(Debug, Clone, Serialize)]
+pub struct HeadingInfo {
+    pub level: u8,
+    pub text: String,
+    pub id: String,
+    pub line: usize,       // 从 1 开始
+    pub pos: usize,        // UTF-16 偏移，与编辑器 (JS 字符串) 的位置一致
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
+/// 大纲树节点
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineNode {
+    #[serde(flatten)]
+    pub heading: HeadingInfo,
+    pub children: Vec<OutlineNode>,
+}
+
+/// 生成稳定的标题锚点：小写、保留字母数字 (含中文)，空白转为 '-'，其余标点去除
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_dash = false;
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            if pending_dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_dash = false;
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() || c == '-' {
+            pending_dash = true;
+        }
+    }
+    if slug.is_empty() {
+        "heading".to_string()
+    } else {
+        slug
+    }
+}
+
+/// 为同一文档中的标题分配唯一锚点，重复的锚点依次追加 -1、-2 ...
+#[derive(Default)]
+pub struct SlugGenerator {
+    used: HashMap<String, usize>,
+}
+
+impl SlugGenerator {
+    pub fn unique(&mut self, base: &str) -> String {
+        let mut candidate = base.to_string();
+        while let Some(count) = self.used.get_mut(&candidate) {
+            *count += 1;
+            candidate = format!("{}-{}", base, count);
+        }
+        self.used.insert(candidate.clone(), 0);
+        candidate
+    }
+
+    /// 标题锚点：优先使用显式 id (`{#id}`)，否则由标题文本生成
+    pub fn heading_id(&mut self, text: &str, explicit_id: Option<&str>) -> String {
+        match explicit_id.map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id) => self.unique(id),
+            None => self.unique(&slugify(text)),
+        }
+    }
+}
+
+/// 收集每个标题的级别、纯文本和显式 id (`# 标题 {#id}`)，按出现顺序
+pub fn collect_headings(events: &[Event<'_>]) -> Vec<(u8, String, Option<String>)> {
+    let mut headings = Vec::new();
+    let mut current: Option<(u8, String, Option<String>)> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::Heading(level, id, _)) => {
+                current = Some((*level as u8, String::new(), id.map(|s| s.to_string())));
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some((level, text, id)) = current.take() {
+                    headings.push((level, text.trim().to_string(), id));
+                }
+            }
+            Event::Text(t) | Event::Code(t) => {
+                if let Some((_, text, _)) = current.as_mut() {
+                    text.push_str(t);
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// 提取文档中的所有标题 (跳过 frontmatter)
+pub fn extract_headings(content: &str) -> Vec<HeadingInfo> {
+    let body_start = split_frontmatter(content).map(|(_, start)| start).unwrap_or(0);
+    let body = &content[body_start..];
+
+    let mut headings = Vec::new();
+    let mut slugs = SlugGenerator::default();
+    let mut current: Option<(u8, String, Option<String>, std::ops::Range<usize>)> = None;
+
+    for (event, range) in Parser::new_ext(body, markdown_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(level, id, _)) => {
+                current = Some((level as u8, String::new(), id.map(|s| s.to_string()), range));
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some((level, text, explicit_id, range)) = current.take() {
+                    let byte_start = body_start + range.start;
+                    let text = text.trim().to_string();
+                    headings.push(HeadingInfo {
+                        level,
+                        id: slugs.heading_id(&text, explicit_id.as_deref()),
+                        text,
+                        line: content[..byte_start].matches('\n').count() + 1,
+                        pos: content[..byte_start].encode_utf16().count(),
+                        byte_start,
+                        byte_end: body_start + range.end,
+                    });
+                }
+            }
+            Event::Text(t) | Event::Code(t) => {
+                if let Some((_, text, _, _)) = current.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// 将扁平标题列表组装为树 (跳级的标题挂在最近的上级下)
+pub fn build_outline_tree(headings: Vec<HeadingInfo>) -> Vec<OutlineNode> {
+    fn attach(nodes: &mut Vec<OutlineNode>, node: OutlineNode) {
+        match nodes.last_mut() {
+            Some(last) if last.heading.level < node.heading.level => attach(&mut last.children, node),
+            _ => nodes.push(node),
+        }
+    }
+
+    let mut roots = Vec::new();
+    for heading in headings {
+        attach(&mut roots, OutlineNode { heading, children: Vec::new() });
+    }
+    roots
+}
+
+/// 生成嵌套的目录 HTML
+pub fn render_toc_html(headings: &[(u8, String, String)]) -> String {
+    if headings.is_empty() {
+        return String::new();
+    }
+    fn render(nodes: &[OutlineNode], out: &mut String) {
+        out.push_str("<ul>");
+        for node in nodes {
+            out.push_str(&format!(
+                "<li><a href=\"#{}\" class=\"toc-link\">{}</a>",
+                escape_html(&node.heading.id),
+                escape_html(&node.heading.text)
+            ));
+            if !node.children.is_empty() {
+                render(&node.children, out);
+            }
+            out.push_str("</li>");
+        }
+        out.push_str("</ul>");
+    }
+
+    let flat: Vec<HeadingInfo> = headings
+        .iter()
+        .map(|(level, text, id)| HeadingInfo {
+            level: *level,
+            text: text.clone(),
+            id: id.clone(),
+            line: 0,
+            pos: 0,
+            byte_start: 0,
+            byte_end: 0,
+        })
+        .collect();
+    let mut html = String::from("<nav class=\"toc\">");
+    render(&build_outline_tree(flat), &mut html);
+    html.push_str("</nav>");
+    html
+}
+
+/// 获取笔记大纲 (标题树)，content 为编辑器中的当前内容
+#[command]
+pub async fn get_note_outline(content: String) -> Result<Vec<OutlineNode>, String> {
+    Ok(build_outline_tree(extract_headings(&content)))
+}
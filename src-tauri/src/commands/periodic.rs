@@ -0,0 +1,288 @@
+// src-tauri/src/commands/periodic.rs
+// 周期笔记 (日记 / 周记 / 月记)
+
+use crate::commands::fs::create_file_with_content;
+use crate::commands::path_utils::to_absolute_path;
+use crate::database::{get_setting, set_setting};
+use crate::AppState;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::{command, State};
+
+/// list_periodic_notes 单次最多返回的周期数，防止前端传入过大的范围
+const MAX_PERIODS_PER_LIST: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodicKind {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl PeriodicKind {
+    fn setting_key(&self) -> &'static str {
+        match self {
+            PeriodicKind::Daily => "periodic.daily",
+            PeriodicKind::Weekly => "periodic.weekly",
+            PeriodicKind::Monthly => "periodic.monthly",
+        }
+    }
+
+    /// 将任意日期归一到所在周期的第一天 (周一 / 每月 1 日)
+    fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            PeriodicKind::Daily => date,
+            PeriodicKind::Weekly => date
+                .checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64))
+                .unwrap_or(date),
+            PeriodicKind::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    fn next_period(&self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            PeriodicKind::Daily => start.checked_add_days(Days::new(1)),
+            PeriodicKind::Weekly => start.checked_add_days(Days::new(7)),
+            PeriodicKind::Monthly => start.checked_add_months(Months::new(1)),
+        }
+    }
+}
+
+/// 周期笔记配置
+/// path_pattern 使用 strftime 语法，例如 `journal/%Y/%m/%Y-%m-%d.md`
+/// template_path 为可选的模板笔记 (相对路径)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodicConfig {
+    pub path_pattern: String,
+    pub template_path: Option<String>,
+}
+
+impl PeriodicConfig {
+    fn default_for(kind: PeriodicKind) -> Self {
+        let path_pattern = match kind {
+            PeriodicKind::Daily => "journal/%Y/%m/%Y-%m-%d.md",
+            PeriodicKind::Weekly => "journal/%G/%G-W%V.md",
+            PeriodicKind::Monthly => "journal/%Y/%Y-%m.md",
+        };
+        PeriodicConfig {
+            path_pattern: path_pattern.to_string(),
+            template_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeriodicNote {
+    pub path: String,
+    pub date: String,
+    pub created: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeriodicNoteEntry {
+    pub date: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("日期格式错误 (应为 YYYY-MM-DD): {} ({})", date, e))
+}
+
+/// 按 strftime 模式格式化日期，模式非法时返回错误而不是 panic
+fn format_with_pattern(date: NaiveDate, pattern: &str) -> Result<String, String> {
+    let items: Vec<Item> = StrftimeItems::new(pattern).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("路径模式包含无效的格式符: {}", pattern));
+    }
+    Ok(date.format_with_items(items.into_iter()).to_string())
+}
+
+/// 根据配置计算周期笔记的相对路径 (统一使用 / 分隔并保证 .md 后缀)
+fn periodic_note_path(config: &PeriodicConfig, date: NaiveDate) -> Result<String, String> {
+    let mut path = format_with_pattern(date, &config.path_pattern)?
+        .replace('\\', "/")
+        .trim_start_matches('/')
+        .to_string();
+    if path.is_empty() || path.ends_with('/') {
+        return Err(format!("路径模式未包含文件名: {}", config.path_pattern));
+    }
+    if path.split('/').any(|segment| segment == "..") {
+        return Err("路径模式不能包含 '..'".to_string());
+    }
+    if !path.ends_with(".md") {
+        path.push_str(".md");
+    }
+    Ok(path)
+}
+
+/// 渲染模板占位符: {{title}}、{{date}}、{{date:FORMAT}}、{{time}}
+fn render_template(template: &str, date: NaiveDate, title: &str) -> String {
+    let re = Regex::new(r"\{\{\s*(\w+)(?::([^}]+))?\s*\}\}").unwrap();
+    re.replace_all(template, |caps: &regex::Captures| {
+        let format = caps.get(2).map(|m| m.as_str().trim());
+        match &caps[1] {
+            "title" => title.to_string(),
+            "date" => format_with_pattern(date, format.unwrap_or("%Y-%m-%d"))
+                .unwrap_or_else(|_| caps[0].to_string()),
+            "time" => {
+                let now = Local::now();
+                let items: Vec<Item> = StrftimeItems::new(format.unwrap_or("%H:%M")).collect();
+                if items.iter().any(|item| matches!(item, Item::Error)) {
+                    caps[0].to_string()
+                } else {
+                    now.format_with_items(items.into_iter()).to_string()
+                }
+            }
+            _ => caps[0].to_string(),
+        }
+    })
+    .to_string()
+}
+
+fn load_config(state: &State<'_, AppState>, kind: PeriodicKind) -> Result<PeriodicConfig, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    Ok(get_setting(&conn, kind.setting_key()).unwrap_or_else(|| PeriodicConfig::default_for(kind)))
+}
+
+#[command]
+pub async fn get_periodic_config(kind: PeriodicKind, state: State<'_, AppState>) -> Result<PeriodicConfig, String> {
+    load_config(&state, kind)
+}
+
+#[command]
+pub async fn set_periodic_config(
+    kind: PeriodicKind,
+    config: PeriodicConfig,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    // 提前校验模式，避免保存一个无法使用的配置
+    periodic_note_path(&config, Local::now().date_naive())?;
+
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    set_setting(&conn, kind.setting_key(), &config).map_err(|e| e.to_string())
+}
+
+/// 打开 (必要时创建) 指定日期所在周期的笔记
+/// date 省略时使用今天
+#[command]
+pub async fn open_periodic_note(
+    root_path: String,
+    kind: PeriodicKind,
+    date: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<PeriodicNote, String> {
+    let date = match date {
+        Some(d) => parse_date(&d)?,
+        None => Local::now().date_naive(),
+    };
+    let period_start = kind.period_start(date);
+    let config = load_config(&state, kind)?;
+    let relative_path = periodic_note_path(&config, period_start)?;
+    let base_path = Path::new(&root_path);
+
+    if to_absolute_path(base_path, Path::new(&relative_path)).exists() {
+        return Ok(PeriodicNote {
+            path: relative_path,
+            date: period_start.format("%Y-%m-%d").to_string(),
+            created: false,
+        });
+    }
+
+    println!("📅 [periodic] 创建周期笔记: {}", relative_path);
+
+    let (relative_dir, file_name) = match relative_path.rsplit_once('/') {
+        Some((dir, name)) => (dir.to_string(), name.to_string()),
+        None => (String::new(), relative_path.clone()),
+    };
+
+    // 1. 确保目录存在，并为新建的每一级目录写入 files 记录
+    if !relative_dir.is_empty() {
+        let absolute_dir = to_absolute_path(base_path, Path::new(&relative_dir));
+        fs::create_dir_all(&absolute_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+
+        let db_pool_lock = state.db_pool.lock().unwrap();
+        if let Some(pool) = db_pool_lock.as_ref() {
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            let mut current = String::new();
+            for segment in relative_dir.split('/') {
+                if !current.is_empty() {
+                    current.push('/');
+                }
+                current.push_str(segment);
+                conn.execute(
+                    "INSERT OR IGNORE INTO files (path, title, is_dir, created_at, updated_at)
+                     VALUES (?1, ?2, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+                    params![current, segment],
+                ).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    // 2. 渲染模板 (没有模板时使用与 create_new_file 相同的默认内容)
+    let title = file_name.trim_end_matches(".md").to_string();
+    let content = match config.template_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        Some(template_path) => {
+            let template_abs = to_absolute_path(base_path, Path::new(template_path));
+            let template = fs::read_to_string(&template_abs)
+                .map_err(|e| format!("读取模板失败 ({}): {}", template_path, e))?;
+            render_template(&template, period_start, &title)
+        }
+        None => format!("# {}\n\n", title),
+    };
+
+    // 3. 走 create_new_file 的创建流程 (加锁、入库、历史、索引)
+    let path = create_file_with_content(root_path, relative_dir, file_name, content, state).await?;
+
+    Ok(PeriodicNote {
+        path,
+        date: period_start.format("%Y-%m-%d").to_string(),
+        created: true,
+    })
+}
+
+/// 列出日期范围内 (含两端) 的所有周期及其笔记是否存在，用于日历导航
+#[command]
+pub async fn list_periodic_notes(
+    root_path: String,
+    kind: PeriodicKind,
+    start_date: String,
+    end_date: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PeriodicNoteEntry>, String> {
+    let start = kind.period_start(parse_date(&start_date)?);
+    let end = parse_date(&end_date)?;
+    if end < start {
+        return Err("结束日期不能早于开始日期".to_string());
+    }
+
+    let config = load_config(&state, kind)?;
+    let base_path = Path::new(&root_path);
+    let mut entries = Vec::new();
+    let mut current = Some(start);
+
+    while let Some(date) = current {
+        if date > end || entries.len() >= MAX_PERIODS_PER_LIST {
+            break;
+        }
+        let path = periodic_note_path(&config, date)?;
+        let exists = to_absolute_path(base_path, Path::new(&path)).is_file();
+        entries.push(PeriodicNoteEntry {
+            date: date.format("%Y-%m-%d").to_string(),
+            path,
+            exists,
+        });
+        current = kind.next_period(date);
+    }
+
+    Ok(entries)
+}
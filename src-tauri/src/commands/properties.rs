@@ -0,0 +1,303 @@
+// src-tauri/src/commands/properties.rs
+// YAML Frontmatter 解析与笔记属性 (properties) API
+
+use crate::commands::fs::save_file;
+use crate::commands::path_utils::to_absolute_path;
+use crate::AppState;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::path::Path;
+use tauri::{command, State};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NoteProperty {
+    pub key: String,
+    pub value: serde_json::Value,
+    pub value_type: String,
+}
+
+/// 拆分 frontmatter，返回 (YAML 文本, 正文起始字节偏移)
+/// frontmatter 必须位于文件开头，以 `---` 开始，以 `---` 或 `...` 结束
+pub fn split_frontmatter(content: &str) -> Option<(&str, usize)> {
+    let bom_len = if content.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+    let text = &content[bom_len..];
+    let first_line_end = text.find('\n')?;
+    if text[..first_line_end].trim_end() != "---" {
+        return None;
+    }
+
+    let yaml_start = bom_len + first_line_end + 1;
+    let mut offset = yaml_start;
+    for line in content[yaml_start..].split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Some((&content[yaml_start..offset], offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// 解析 frontmatter 为 YAML Mapping；没有 frontmatter 或格式错误时返回 None
+pub fn parse_frontmatter(content: &str) -> Option<Mapping> {
+    let (yaml, _) = split_frontmatter(content)?;
+    if yaml.trim().is_empty() {
+        return Some(Mapping::new());
+    }
+    match serde_yaml::from_str::<YamlValue>(yaml) {
+        Ok(YamlValue::Mapping(mapping)) => Some(mapping),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("⚠️ [properties] frontmatter 解析失败: {}", e);
+            None
+        }
+    }
+}
+
+/// frontmatter 中的 `title:` (非空字符串)
+pub fn frontmatter_title(content: &str) -> Option<String> {
+    parse_frontmatter(content)?
+        .get("title")
+        .and_then(yaml_scalar_to_string)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// frontmatter 中的别名：`aliases:` (或 `alias:`)，可以是列表或单个字符串
+pub fn frontmatter_aliases(mapping: &Mapping) -> Vec<String> {
+    let value = mapping.get("aliases").or_else(|| mapping.get("alias"));
+    let values: Vec<&YamlValue> = match value {
+        Some(YamlValue::Sequence(items)) => items.iter().collect(),
+        Some(other) => vec![other],
+        None => Vec::new(),
+    };
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in values.into_iter().filter_map(yaml_scalar_to_string) {
+        let alias = alias.trim().to_string();
+        if !alias.is_empty() && !aliases.iter().any(|a| a.eq_ignore_ascii_case(&alias)) {
+            aliases.push(alias);
+        }
+    }
+    aliases
+}
+
+/// 笔记标题：优先使用 frontmatter 的 title，否则使用文件名 (不含扩展名)
+pub fn note_title(relative_path: &str, content: &str) -> String {
+    frontmatter_title(content).unwrap_or_else(|| {
+        Path::new(relative_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string()
+    })
+}
+
+fn yaml_scalar_to_string(value: &YamlValue) -> Option<String> {
+    match value {
+        YamlValue::String(s) => Some(s.clone()),
+        YamlValue::Number(n) => Some(n.to_string()),
+        YamlValue::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn is_iso_date(s: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+        || chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").is_ok()
+        || chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").is_ok()
+}
+
+/// 将 YAML 值归类为 (类型, 文本值, 数值)
+/// 文本值用于比较/显示，数值用于数字排序与范围比较
+fn classify_value(value: &YamlValue) -> (&'static str, String, Option<f64>) {
+    match value {
+        YamlValue::Null => ("null", String::new(), None),
+        YamlValue::Bool(b) => ("boolean", b.to_string(), Some(if *b { 1.0 } else { 0.0 })),
+        YamlValue::Number(n) => ("number", n.to_string(), n.as_f64()),
+        YamlValue::String(s) if is_iso_date(s) => ("date", s.clone(), None),
+        YamlValue::String(s) => ("text", s.clone(), None),
+        YamlValue::Sequence(_) => (
+            "list",
+            serde_json::to_string(&yaml_to_json(value)).unwrap_or_default(),
+            None,
+        ),
+        YamlValue::Mapping(_) => (
+            "object",
+            serde_json::to_string(&yaml_to_json(value)).unwrap_or_default(),
+            None,
+        ),
+        YamlValue::Tagged(tagged) => classify_value(&tagged.value),
+    }
+}
+
+fn yaml_to_json(value: &YamlValue) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// 索引时调用：解析 frontmatter，重建该文件的 note_properties 和 aliases，并同步 files.title
+pub fn update_properties_for_file(
+    conn: &mut Connection,
+    root_path: &str,
+    relative_path: &str,
+) -> Result<(), String> {
+    let absolute_path = to_absolute_path(Path::new(root_path), Path::new(relative_path));
+    let content = match std::fs::read_to_string(&absolute_path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("  🏷️ [properties] 读取文件失败: {}: {}", relative_path, e);
+            return Ok(());
+        }
+    };
+
+    let file_id: i64 = match conn
+        .query_row("SELECT id FROM files WHERE path = ?1", params![relative_path], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("查询文件ID失败: {}", e))?
+    {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let mapping = parse_frontmatter(&content).unwrap_or_default();
+    let title = note_title(relative_path, &content);
+
+    let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+    tx.execute("DELETE FROM note_properties WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("删除旧属性失败: {}", e))?;
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT OR REPLACE INTO note_properties (file_id, key, value_type, value_text, value_num, value_json)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| format!("准备属性插入失败: {}", e))?;
+        for (key, value) in &mapping {
+            let key = match yaml_scalar_to_string(key) {
+                Some(k) if !k.trim().is_empty() => k.trim().to_string(),
+                _ => continue,
+            };
+            let (value_type, value_text, value_num) = classify_value(value);
+            let value_json = serde_json::to_string(&yaml_to_json(value)).unwrap_or_else(|_| "null".to_string());
+            insert
+                .execute(params![file_id, key, value_type, value_text, value_num, value_json])
+                .map_err(|e| format!("插入属性失败 ({}): {}", key, e))?;
+        }
+    }
+    tx.execute("UPDATE files SET title = ?1 WHERE id = ?2", params![title, file_id])
+        .map_err(|e| format!("更新标题失败: {}", e))?;
+
+    let aliases = frontmatter_aliases(&mapping);
+    tx.execute("DELETE FROM aliases WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("删除旧别名失败: {}", e))?;
+    for alias in &aliases {
+        tx.execute(
+            "INSERT OR IGNORE INTO aliases (file_id, alias) VALUES (?1, ?2)",
+            params![file_id, alias],
+        )
+        .map_err(|e| format!("插入别名失败 ({}): {}", alias, e))?;
+    }
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
+
+    println!("  🏷️ [properties] {} 个属性, 标题: {}, 别名: {:?}", mapping.len(), title, aliases);
+    Ok(())
+}
+
+/// 用新的 frontmatter 替换 (或插入) 文件头部，正文保持不变
+fn replace_frontmatter(content: &str, mapping: &Mapping) -> Result<String, String> {
+    let body = match split_frontmatter(content) {
+        Some((_, body_start)) => &content[body_start..],
+        None => content,
+    };
+    if mapping.is_empty() {
+        return Ok(body.to_string());
+    }
+    let yaml = serde_yaml::to_string(mapping).map_err(|e| format!("序列化 frontmatter 失败: {}", e))?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+async fn rewrite_frontmatter<F>(
+    root_path: String,
+    relative_path: String,
+    state: State<'_, AppState>,
+    edit: F,
+) -> Result<(), String>
+where
+    F: FnOnce(&mut Mapping),
+{
+    let absolute_path = to_absolute_path(Path::new(&root_path), Path::new(&relative_path));
+    let content = std::fs::read_to_string(&absolute_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    if split_frontmatter(&content).is_some() && parse_frontmatter(&content).is_none() {
+        return Err("frontmatter 格式错误，请先手动修复".to_string());
+    }
+
+    let mut mapping = parse_frontmatter(&content).unwrap_or_default();
+    edit(&mut mapping);
+    let new_content = replace_frontmatter(&content, &mapping)?;
+    if new_content == content {
+        return Ok(());
+    }
+    // 通过 save_file 走统一的保存流程 (锁、链接、历史、索引)
+    save_file(root_path, relative_path, new_content, state).await
+}
+
+#[command]
+pub async fn get_note_properties(relative_path: String, state: State<'_, AppState>) -> Result<Vec<NoteProperty>, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.key, p.value_json, p.value_type FROM note_properties p
+             INNER JOIN files f ON f.id = p.file_id
+             WHERE f.path = ?1 ORDER BY p.rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let props_iter = stmt
+        .query_map(params![relative_path], |row| {
+            let value_json: String = row.get(1)?;
+            Ok(NoteProperty {
+                key: row.get(0)?,
+                value: serde_json::from_str(&value_json).unwrap_or(serde_json::Value::Null),
+                value_type: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut props = Vec::new();
+    for prop in props_iter {
+        props.push(prop.map_err(|e| e.to_string())?);
+    }
+    Ok(props)
+}
+
+/// 设置单个属性 (不存在则新增)，只改写 frontmatter，不触碰正文
+#[command]
+pub async fn set_note_property(
+    root_path: String,
+    relative_path: String,
+    key: String,
+    value: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("属性名不能为空".to_string());
+    }
+    let yaml_value = serde_yaml::to_value(&value).map_err(|e| format!("属性值无效: {}", e))?;
+    rewrite_frontmatter(root_path, relative_path, state, move |mapping| {
+        mapping.insert(YamlValue::String(key), yaml_value);
+    })
+    .await
+}
+
+#[command]
+pub async fn remove_note_property(
+    root_path: String,
+    relative_path: String,
+    key: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    rewrite_frontmatter(root_path, relative_path, state, move |mapping| {
+        mapping.shift_remove(key.trim());
+    })
+    .await
+}
@@ -0,0 +1,764 @@
+// src-tauri/src/commands/query.rs
+// 笔记元数据查询语言 (类 Dataview)
+//
+// 语法:
+//   LIST [字段] | TABLE 字段 [AS "列名"], ...
+//   [FROM 来源]            #tag | "文件夹" | [[笔记]] (链接到该笔记的笔记)，可用 AND / OR / NOT / 括号组合
+//   [WHERE 条件]           字段 (= != < <= > >= CONTAINS) 值，可用 AND / OR / NOT / 括号组合，单独的字段表示“存在且非空”
+//   [SORT 字段 [ASC|DESC], ...]
+//   [LIMIT n]
+//
+// 字段: frontmatter 属性名 (如 status、due)，或内置字段
+//   file.path / file.title / file.name / file.size / file.words / file.mtime / file.ctime
+//   file.tags / file.inlinks / file.outlinks
+// 值: "字符串" | 数字 | true | false | null | 2026-10-20 (日期按文本比较)
+//
+// 示例: LIST FROM #project WHERE status = "open" SORT due ASC
+
+use crate::commands::utils::escape_like;
+use crate::AppState;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use serde::Serialize;
+use tauri::{command, State};
+
+const DEFAULT_LIMIT: i64 = 1000;
+
+// ============================================================================
+// 1. 词法分析
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Str(String),
+    Number(f64),
+    Date(String),
+    Tag(String),
+    Link(String),
+    Op(String),
+    LParen,
+    RParen,
+    Comma,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    pos: usize, // 字符位置 (从 1 开始)，用于错误提示
+}
+
+fn query_error(pos: usize, message: impl AsRef<str>) -> String {
+    format!("查询语法错误 (位置 {}): {}", pos, message.as_ref())
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let pos = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        match c {
+            '(' => { tokens.push(Token { kind: TokenKind::LParen, pos }); i += 1; }
+            ')' => { tokens.push(Token { kind: TokenKind::RParen, pos }); i += 1; }
+            ',' => { tokens.push(Token { kind: TokenKind::Comma, pos }); i += 1; }
+            '=' => { tokens.push(Token { kind: TokenKind::Op("=".into()), pos }); i += 1; }
+            '!' | '<' | '>' => {
+                let op = if chars.get(i + 1) == Some(&'=') {
+                    i += 2;
+                    format!("{}=", c)
+                } else if c == '!' {
+                    return Err(query_error(pos, "'!' 后应为 '='"));
+                } else {
+                    i += 1;
+                    c.to_string()
+                };
+                tokens.push(Token { kind: TokenKind::Op(op), pos });
+            }
+            '"' | '\'' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(query_error(pos, "字符串缺少结束引号")),
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&ch) if ch == quote => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token { kind: TokenKind::Str(value), pos });
+            }
+            '#' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && (is_ident_char(chars[i]) || chars[i] == '/') {
+                    i += 1;
+                }
+                if i == start {
+                    return Err(query_error(pos, "'#' 后缺少标签名"));
+                }
+                let tag: String = chars[start..i].iter().collect();
+                tokens.push(Token { kind: TokenKind::Tag(tag.to_lowercase()), pos });
+            }
+            '[' if chars.get(i + 1) == Some(&'[') => {
+                let start = i + 2;
+                let mut end = start;
+                while end + 1 < chars.len() && !(chars[end] == ']' && chars[end + 1] == ']') {
+                    end += 1;
+                }
+                if end + 1 >= chars.len() {
+                    return Err(query_error(pos, "链接缺少结束的 ']]'"));
+                }
+                let target: String = chars[start..end].iter().collect();
+                tokens.push(Token { kind: TokenKind::Link(target.trim().to_string()), pos });
+                i = end + 2;
+            }
+            _ if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | '-' | ':' | 'T')) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let kind = if let Ok(n) = text.parse::<f64>() {
+                    TokenKind::Number(n)
+                } else if chrono::NaiveDate::parse_from_str(&text, "%Y-%m-%d").is_ok()
+                    || chrono::NaiveDateTime::parse_from_str(&text, "%Y-%m-%dT%H:%M:%S").is_ok()
+                {
+                    TokenKind::Date(text)
+                } else {
+                    return Err(query_error(pos, format!("无法识别的数字或日期: {}", text)));
+                };
+                tokens.push(Token { kind, pos });
+            }
+            _ if is_ident_char(c) => {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                tokens.push(Token { kind: TokenKind::Ident(ident), pos });
+            }
+            _ => return Err(query_error(pos, format!("无法识别的字符 '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+// ============================================================================
+// 2. 语法分析
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryKind {
+    List,
+    Table,
+}
+
+#[derive(Debug, Clone)]
+enum Literal {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Tag(String),
+    Folder(String),
+    LinkedTo(String),
+    And(Box<Source>, Box<Source>),
+    Or(Box<Source>, Box<Source>),
+    Not(Box<Source>),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Compare { field: String, op: String, value: Literal },
+    Contains { field: String, value: Literal },
+    Exists(String),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    field: String,
+    label: String,
+}
+
+#[derive(Debug, Clone)]
+struct ParsedQuery {
+    kind: QueryKind,
+    columns: Vec<Column>,
+    source: Option<Source>,
+    filter: Option<Expr>,
+    sort: Vec<(String, bool)>, // (字段, 是否降序)
+    limit: i64,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    input_len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn current_pos(&self) -> usize {
+        self.peek().map(|t| t.pos).unwrap_or(self.input_len + 1)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Ident(s), .. }) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn at_clause_keyword(&self) -> bool {
+        ["FROM", "WHERE", "SORT", "LIMIT"].iter().any(|k| self.peek_keyword(k))
+    }
+
+    fn expect_field(&mut self) -> Result<String, String> {
+        let pos = self.current_pos();
+        match self.next() {
+            Some(Token { kind: TokenKind::Ident(name), .. }) => Ok(name),
+            Some(_) => Err(query_error(pos, "此处应为字段名")),
+            None => Err(query_error(pos, "查询意外结束，缺少字段名")),
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<ParsedQuery, String> {
+        let kind = if self.eat_keyword("LIST") {
+            QueryKind::List
+        } else if self.eat_keyword("TABLE") {
+            QueryKind::Table
+        } else {
+            return Err(query_error(self.current_pos(), "查询必须以 LIST 或 TABLE 开头"));
+        };
+
+        let mut columns = Vec::new();
+        if !self.at_clause_keyword() && self.peek().is_some() {
+            loop {
+                let field = self.expect_field()?;
+                let label = if self.eat_keyword("AS") {
+                    let pos = self.current_pos();
+                    match self.next() {
+                        Some(Token { kind: TokenKind::Str(s), .. }) | Some(Token { kind: TokenKind::Ident(s), .. }) => s,
+                        _ => return Err(query_error(pos, "AS 后应为列名")),
+                    }
+                } else {
+                    field.clone()
+                };
+                columns.push(Column { field, label });
+                if kind == QueryKind::List || !matches!(self.peek(), Some(Token { kind: TokenKind::Comma, .. })) {
+                    break;
+                }
+                self.pos += 1;
+            }
+        }
+
+        let mut query = ParsedQuery { kind, columns, source: None, filter: None, sort: Vec::new(), limit: DEFAULT_LIMIT };
+
+        while let Some(token) = self.peek().cloned() {
+            if self.eat_keyword("FROM") {
+                query.source = Some(self.parse_source_or()?);
+            } else if self.eat_keyword("WHERE") {
+                query.filter = Some(self.parse_expr_or()?);
+            } else if self.eat_keyword("SORT") {
+                loop {
+                    let field = self.expect_field()?;
+                    let descending = if self.eat_keyword("DESC") {
+                        true
+                    } else {
+                        self.eat_keyword("ASC");
+                        false
+                    };
+                    query.sort.push((field, descending));
+                    if !matches!(self.peek(), Some(Token { kind: TokenKind::Comma, .. })) {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else if self.eat_keyword("LIMIT") {
+                let pos = self.current_pos();
+                match self.next() {
+                    Some(Token { kind: TokenKind::Number(n), .. }) if n >= 0.0 && n.fract() == 0.0 => query.limit = n as i64,
+                    _ => return Err(query_error(pos, "LIMIT 后应为非负整数")),
+                }
+            } else {
+                return Err(query_error(token.pos, "此处应为 FROM、WHERE、SORT 或 LIMIT"));
+            }
+        }
+        Ok(query)
+    }
+
+    fn parse_source_or(&mut self) -> Result<Source, String> {
+        let mut left = self.parse_source_and()?;
+        while self.eat_keyword("OR") {
+            let right = self.parse_source_and()?;
+            left = Source::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_source_and(&mut self) -> Result<Source, String> {
+        let mut left = self.parse_source_unary()?;
+        while self.eat_keyword("AND") {
+            let right = self.parse_source_unary()?;
+            left = Source::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_source_unary(&mut self) -> Result<Source, String> {
+        if self.eat_keyword("NOT") {
+            return Ok(Source::Not(Box::new(self.parse_source_unary()?)));
+        }
+        let pos = self.current_pos();
+        match self.next() {
+            Some(Token { kind: TokenKind::Tag(tag), .. }) => Ok(Source::Tag(tag)),
+            Some(Token { kind: TokenKind::Str(folder), .. }) => Ok(Source::Folder(folder)),
+            Some(Token { kind: TokenKind::Link(target), .. }) => Ok(Source::LinkedTo(target)),
+            Some(Token { kind: TokenKind::LParen, .. }) => {
+                let inner = self.parse_source_or()?;
+                self.expect_rparen()?;
+                Ok(inner)
+            }
+            _ => Err(query_error(pos, "FROM 后应为 #标签、\"文件夹\" 或 [[笔记]]")),
+        }
+    }
+
+    fn expect_rparen(&mut self) -> Result<(), String> {
+        let pos = self.current_pos();
+        match self.next() {
+            Some(Token { kind: TokenKind::RParen, .. }) => Ok(()),
+            _ => Err(query_error(pos, "缺少右括号 ')'")),
+        }
+    }
+
+    fn parse_expr_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_expr_and()?;
+        while self.eat_keyword("OR") {
+            let right = self.parse_expr_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_expr_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_expr_unary()?;
+        while self.eat_keyword("AND") {
+            let right = self.parse_expr_unary()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_expr_unary(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_expr_unary()?)));
+        }
+        if matches!(self.peek(), Some(Token { kind: TokenKind::LParen, .. })) {
+            self.pos += 1;
+            let inner = self.parse_expr_or()?;
+            self.expect_rparen()?;
+            return Ok(inner);
+        }
+
+        let field = self.expect_field()?;
+        if self.eat_keyword("CONTAINS") {
+            let value = self.parse_literal()?;
+            return Ok(Expr::Contains { field, value });
+        }
+        match self.peek().cloned() {
+            Some(Token { kind: TokenKind::Op(op), .. }) => {
+                self.pos += 1;
+                let value = self.parse_literal()?;
+                Ok(Expr::Compare { field, op, value })
+            }
+            _ => Ok(Expr::Exists(field)),
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, String> {
+        let pos = self.current_pos();
+        match self.next() {
+            Some(Token { kind: TokenKind::Str(s), .. }) => Ok(Literal::Text(s)),
+            Some(Token { kind: TokenKind::Date(s), .. }) => Ok(Literal::Text(s)),
+            Some(Token { kind: TokenKind::Tag(s), .. }) => Ok(Literal::Text(s)),
+            Some(Token { kind: TokenKind::Link(s), .. }) => Ok(Literal::Text(s)),
+            Some(Token { kind: TokenKind::Number(n), .. }) => Ok(Literal::Number(n)),
+            Some(Token { kind: TokenKind::Ident(s), .. }) => match s.to_lowercase().as_str() {
+                "true" => Ok(Literal::Bool(true)),
+                "false" => Ok(Literal::Bool(false)),
+                "null" => Ok(Literal::Null),
+                _ => Ok(Literal::Text(s)),
+            },
+            _ => Err(query_error(pos, "此处应为比较值")),
+        }
+    }
+}
+
+fn parse_query(input: &str) -> Result<ParsedQuery, String> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0, input_len: input.chars().count() };
+    parser.parse_query()
+}
+
+// ============================================================================
+// 3. 编译为 SQL
+// ============================================================================
+
+/// 带参数的 SQL 片段
+struct SqlFragment {
+    sql: String,
+    params: Vec<SqlValue>,
+}
+
+impl SqlFragment {
+    fn new(sql: impl Into<String>, params: Vec<SqlValue>) -> Self {
+        SqlFragment { sql: sql.into(), params }
+    }
+}
+
+/// 内置字段的 SQL 表达式；None 表示这是 frontmatter 属性
+fn builtin_field(field: &str) -> Option<&'static str> {
+    match field.to_lowercase().as_str() {
+        "file.path" => Some("f.path"),
+        "file.title" => Some("f.title"),
+        "file.name" => Some("replace(f.path, rtrim(f.path, replace(f.path, '/', '')), '')"),
+        "file.size" => Some("f.size"),
+        "file.words" => Some("f.word_count"),
+        "file.mtime" => Some("f.last_modified"),
+        "file.ctime" => Some("f.created_at"),
+        "file.tags" => Some(
+            "(SELECT group_concat(t.name, ',') FROM file_tags ft INNER JOIN tags t ON t.id = ft.tag_id WHERE ft.file_id = f.id)",
+        ),
+        "file.inlinks" => Some("(SELECT COUNT(DISTINCT l.source_file_id) FROM links l WHERE l.target_file_id = f.id)"),
+        "file.outlinks" => Some("(SELECT COUNT(DISTINCT l.target_file_id) FROM links l WHERE l.source_file_id = f.id)"),
+        _ => None,
+    }
+}
+
+fn property_column(field: &str, column: &str) -> SqlFragment {
+    SqlFragment::new(
+        format!("(SELECT p.{} FROM note_properties p WHERE p.file_id = f.id AND p.key = ?)", column),
+        vec![SqlValue::Text(field.to_string())],
+    )
+}
+
+/// 用于比较的字段表达式：数字比较使用 value_num，其余使用 value_text
+fn field_for_compare(field: &str, numeric: bool) -> SqlFragment {
+    match builtin_field(field) {
+        Some(sql) => SqlFragment::new(sql, vec![]),
+        None => property_column(field, if numeric { "value_num" } else { "value_text" }),
+    }
+}
+
+/// 用于排序的字段表达式：数字属性按数值排序，其余按文本排序
+fn field_for_sort(field: &str) -> SqlFragment {
+    match builtin_field(field) {
+        Some(sql) => SqlFragment::new(sql, vec![]),
+        None => SqlFragment::new(
+            "(SELECT COALESCE(p.value_num, p.value_text) FROM note_properties p WHERE p.file_id = f.id AND p.key = ?)",
+            vec![SqlValue::Text(field.to_string())],
+        ),
+    }
+}
+
+fn compile_source(source: &Source) -> SqlFragment {
+    match source {
+        Source::Tag(tag) => SqlFragment::new(
+            "(f.id IN (SELECT ft.file_id FROM file_tags ft INNER JOIN tags t ON t.id = ft.tag_id
+                       WHERE t.name = ? OR t.name LIKE ? || '/%' ESCAPE '\\')
+              OR f.id IN (SELECT p.file_id FROM note_properties p
+                          WHERE p.key = 'tags' AND (
+                              (p.value_type = 'list' AND EXISTS (SELECT 1 FROM json_each(p.value_json) j
+                                                                 WHERE lower(trim(j.value, '#')) = ?))
+                              OR (p.value_type = 'text' AND lower(trim(p.value_text, '#')) = ?))))",
+            vec![
+                SqlValue::Text(tag.clone()),
+                SqlValue::Text(escape_like(tag)),
+                SqlValue::Text(tag.clone()),
+                SqlValue::Text(tag.clone()),
+            ],
+        ),
+        Source::Folder(folder) => {
+            let folder = folder.trim().trim_matches('/').replace('\\', "/");
+            if folder.is_empty() {
+                SqlFragment::new("1", vec![])
+            } else {
+                SqlFragment::new("f.path LIKE ? || '/%' ESCAPE '\\'", vec![SqlValue::Text(escape_like(&folder))])
+            }
+        }
+        // 目标按标题或文件路径 (整个路径或以 `/` 分隔的后缀) 匹配，与 links::find_link_targets 一致
+        Source::LinkedTo(target) => SqlFragment::new(
+            "f.id IN (SELECT l.source_file_id FROM links l WHERE l.target_file_id IN
+                      (SELECT id FROM files WHERE title = ? OR path = ? OR path LIKE '%/' || ? ESCAPE '\\'))",
+            vec![
+                SqlValue::Text(target.clone()),
+                SqlValue::Text(format!("{}.md", target)),
+                SqlValue::Text(escape_like(&format!("{}.md", target))),
+            ],
+        ),
+        Source::And(a, b) => combine(compile_source(a), "AND", compile_source(b)),
+        Source::Or(a, b) => combine(compile_source(a), "OR", compile_source(b)),
+        Source::Not(inner) => {
+            let inner = compile_source(inner);
+            SqlFragment::new(format!("NOT ({})", inner.sql), inner.params)
+        }
+    }
+}
+
+fn combine(left: SqlFragment, op: &str, right: SqlFragment) -> SqlFragment {
+    let mut params = left.params;
+    params.extend(right.params);
+    SqlFragment::new(format!("({} {} {})", left.sql, op, right.sql), params)
+}
+
+fn literal_param(value: &Literal) -> SqlValue {
+    match value {
+        Literal::Text(s) => SqlValue::Text(s.clone()),
+        Literal::Number(n) => SqlValue::Real(*n),
+        Literal::Bool(b) => SqlValue::Real(if *b { 1.0 } else { 0.0 }),
+        Literal::Null => SqlValue::Null,
+    }
+}
+
+fn compile_expr(expr: &Expr) -> Result<SqlFragment, String> {
+    Ok(match expr {
+        Expr::Compare { field, op, value } => {
+            let numeric = matches!(value, Literal::Number(_) | Literal::Bool(_));
+            let lhs = field_for_compare(field, numeric);
+            let mut params = lhs.params;
+            let sql = match (value, op.as_str()) {
+                (Literal::Null, "=") => format!("{} IS NULL", lhs.sql),
+                (Literal::Null, "!=") => format!("{} IS NOT NULL", lhs.sql),
+                (Literal::Null, _) => return Err(format!("null 只能使用 = 或 != 比较 (字段 {})", field)),
+                (_, "!=") => {
+                    // 属性不存在也视为“不等于”，需要两次引用字段
+                    let again = field_for_compare(field, numeric);
+                    params.extend(again.params);
+                    params.push(literal_param(value));
+                    format!("({} IS NULL OR {} <> ?)", lhs.sql, again.sql)
+                }
+                (_, op) => {
+                    params.push(literal_param(value));
+                    format!("{} {} ?", lhs.sql, op)
+                }
+            };
+            SqlFragment::new(sql, params)
+        }
+        Expr::Contains { field, value } => {
+            let needle = match value {
+                Literal::Text(s) => s.clone(),
+                Literal::Number(n) => n.to_string(),
+                Literal::Bool(b) => b.to_string(),
+                Literal::Null => return Err(format!("CONTAINS 不能与 null 一起使用 (字段 {})", field)),
+            };
+            match builtin_field(field) {
+                Some(sql) if field.eq_ignore_ascii_case("file.tags") => SqlFragment::new(
+                    format!("instr(',' || COALESCE({}, '') || ',', ',' || lower(?) || ',') > 0", sql),
+                    vec![SqlValue::Text(needle)],
+                ),
+                Some(sql) => SqlFragment::new(
+                    format!("instr(lower(COALESCE({}, '')), lower(?)) > 0", sql),
+                    vec![SqlValue::Text(needle)],
+                ),
+                None => SqlFragment::new(
+                    "EXISTS (SELECT 1 FROM note_properties p WHERE p.file_id = f.id AND p.key = ? AND (
+                         (p.value_type = 'list' AND EXISTS (SELECT 1 FROM json_each(p.value_json) j WHERE j.value = ?))
+                         OR (p.value_type <> 'list' AND instr(lower(p.value_text), lower(?)) > 0)))",
+                    vec![SqlValue::Text(field.clone()), SqlValue::Text(needle.clone()), SqlValue::Text(needle)],
+                ),
+            }
+        }
+        Expr::Exists(field) => match builtin_field(field) {
+            Some(sql) => SqlFragment::new(format!("COALESCE({}, '') <> ''", sql), vec![]),
+            None => SqlFragment::new(
+                "EXISTS (SELECT 1 FROM note_properties p WHERE p.file_id = f.id AND p.key = ?
+                         AND p.value_type <> 'null' AND COALESCE(p.value_text, '') <> '')",
+                vec![SqlValue::Text(field.clone())],
+            ),
+        },
+        Expr::And(a, b) => combine(compile_expr(a)?, "AND", compile_expr(b)?),
+        Expr::Or(a, b) => combine(compile_expr(a)?, "OR", compile_expr(b)?),
+        Expr::Not(inner) => {
+            let inner = compile_expr(inner)?;
+            SqlFragment::new(format!("NOT ({})", inner.sql), inner.params)
+        }
+    })
+}
+
+// ============================================================================
+// 4. 执行
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct NoteQueryRow {
+    pub path: String,
+    pub title: String,
+    pub values: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteQueryResult {
+    pub kind: QueryKind,
+    pub columns: Vec<String>,
+    pub rows: Vec<NoteQueryRow>,
+}
+
+fn sql_value_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => serde_json::json!(i),
+        ValueRef::Real(r) => serde_json::json!(r),
+        ValueRef::Text(t) => serde_json::Value::String(String::from_utf8_lossy(t).to_string()),
+        ValueRef::Blob(_) => serde_json::Value::Null,
+    }
+}
+
+/// 带有指定标签 (含子标签及 frontmatter 中的 tags) 的笔记路径，供全文搜索的 tag: 字段使用
+pub fn paths_with_tag(conn: &Connection, tag: &str) -> Result<Vec<String>, String> {
+    let fragment = compile_source(&Source::Tag(tag.trim_start_matches('#').to_lowercase()));
+    let sql = format!("SELECT f.path FROM files f WHERE COALESCE(f.is_dir, 0) = 0 AND ({})", fragment.sql);
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let paths = stmt
+        .query_map(params_from_iter(fragment.params), |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(paths)
+}
+
+/// 在给定连接上执行查询 (供 run_note_query 和 parse_markdown 的 ```query 代码块共用)
+pub fn execute_note_query(conn: &Connection, source: &str) -> Result<NoteQueryResult, String> {
+    let query = parse_query(source)?;
+
+    // SELECT 列: id, path, title, 然后是每个用户列
+    // frontmatter 属性取 value_json，保证返回给前端的是原始类型
+    let mut select_sql = String::from("SELECT f.path, f.title");
+    let mut params: Vec<SqlValue> = Vec::new();
+    let mut column_is_json = Vec::new();
+    for column in &query.columns {
+        match builtin_field(&column.field) {
+            Some(sql) => {
+                select_sql.push_str(&format!(", {}", sql));
+                column_is_json.push(false);
+            }
+            None => {
+                let fragment = property_column(&column.field, "value_json");
+                select_sql.push_str(&format!(", {}", fragment.sql));
+                params.extend(fragment.params);
+                column_is_json.push(true);
+            }
+        }
+    }
+
+    let mut sql = format!("{} FROM files f WHERE COALESCE(f.is_dir, 0) = 0", select_sql);
+    if let Some(source) = &query.source {
+        let fragment = compile_source(source);
+        sql.push_str(&format!(" AND ({})", fragment.sql));
+        params.extend(fragment.params);
+    }
+    if let Some(filter) = &query.filter {
+        let fragment = compile_expr(filter)?;
+        sql.push_str(&format!(" AND ({})", fragment.sql));
+        params.extend(fragment.params);
+    }
+
+    let mut order_parts = Vec::new();
+    for (field, descending) in &query.sort {
+        let fragment = field_for_sort(field);
+        // 缺少该字段的笔记始终排在最后
+        order_parts.push(format!("({}) IS NULL", fragment.sql));
+        params.extend(fragment.params.iter().cloned());
+        order_parts.push(format!("{} {}", fragment.sql, if *descending { "DESC" } else { "ASC" }));
+        params.extend(fragment.params);
+    }
+    order_parts.push("f.title COLLATE NOCASE ASC".to_string());
+    sql.push_str(&format!(" ORDER BY {} LIMIT {}", order_parts.join(", "), query.limit));
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("查询编译失败: {}", e))?;
+    let column_count = query.columns.len();
+    let rows_iter = stmt
+        .query_map(params_from_iter(params), |row| {
+            let path: String = row.get(0)?;
+            let title: Option<String> = row.get(1)?;
+            let mut values = Vec::with_capacity(column_count);
+            for (i, is_json) in column_is_json.iter().enumerate() {
+                let raw = row.get_ref(i + 2)?;
+                let value = match (is_json, raw) {
+                    (true, ValueRef::Text(t)) => serde_json::from_slice(t).unwrap_or(serde_json::Value::Null),
+                    (_, raw) => sql_value_to_json(raw),
+                };
+                values.push(value);
+            }
+            Ok(NoteQueryRow {
+                title: title.unwrap_or_else(|| path.clone()),
+                path,
+                values,
+            })
+        })
+        .map_err(|e| format!("执行查询失败: {}", e))?;
+
+    let mut rows = Vec::new();
+    for row in rows_iter {
+        rows.push(row.map_err(|e| e.to_string())?);
+    }
+
+    Ok(NoteQueryResult {
+        kind: query.kind,
+        columns: query.columns.into_iter().map(|c| c.label).collect(),
+        rows,
+    })
+}
+
+#[command]
+pub async fn run_note_query(query: String, state: State<'_, AppState>) -> Result<NoteQueryResult, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    execute_note_query(&conn, &query)
+}
@@ -0,0 +1,279 @@
+// src-tauri/src/commands/quick_open.rs
+// 快速打开：类似 fzf 的子序列模糊匹配，对所有笔记的标题和路径打分并给出高亮范围
+// 含汉字的标题同时按全拼和首字母匹配，如 "kfrz"、"kaifa" 都能找到 "开发日志"
+
+use crate::search_core::{pinyin_forms, PinyinForm};
+use crate::AppState;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+use tauri::{command, State};
+
+const DEFAULT_QUICK_OPEN_LIMIT: usize = 50;
+
+/// 打分参数 (参考 fzf)：每个匹配字符得分，连续匹配和词首匹配有额外加分，间隔扣分
+const SCORE_MATCH: i32 = 16;
+const GAP_START: i32 = -3;
+const GAP_EXTENSION: i32 = -1;
+const BONUS_CONSECUTIVE: i32 = 8;
+const BONUS_PATH_SEPARATOR: i32 = 10; // 紧跟在 / 之后 (文件名、目录名开头)
+const BONUS_BOUNDARY: i32 = 8;        // 开头或紧跟在空格、_、-、. 之后
+const BONUS_CAMEL: i32 = 6;           // 小写到大写、字母到数字的切换处
+const FIRST_CHAR_MULTIPLIER: i32 = 2; // 查询首字符落在词首时加倍
+
+/// 标题与路径都匹配时，标题上的匹配额外加分
+const BONUS_TITLE: i32 = 4;
+
+/// 参与打分的最大字符数，超长路径只取前面部分
+const MAX_TEXT_CHARS: usize = 512;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickOpenItem {
+    pub path: String,
+    pub title: String,
+    pub score: i32,
+    pub title_ranges: Vec<(usize, usize)>, // 标题中匹配的范围 [start, end)，UTF-16 偏移
+    pub path_ranges: Vec<(usize, usize)>,  // 路径中匹配的范围
+}
+
+/// 一次匹配：得分及匹配到的字符下标
+struct FuzzyMatch {
+    score: i32,
+    positions: Vec<usize>,
+}
+
+fn char_bonus(prev: Option<char>, current: char) -> i32 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some('/') | Some('\\') => BONUS_PATH_SEPARATOR,
+        Some(c) if c.is_whitespace() || matches!(c, '_' | '-' | '.' | '(' | '[') => BONUS_BOUNDARY,
+        Some(c) if c.is_lowercase() && current.is_uppercase() => BONUS_CAMEL,
+        Some(c) if !c.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+fn chars_equal(a: char, b: char, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        a == b
+    } else {
+        a == b || a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+/// 在 text 中按子序列匹配 pattern，求得分最高的对齐方式 (动态规划，O(模式长度 × 文本长度))
+fn fuzzy_match(pattern: &[char], text: &str, case_sensitive: bool) -> Option<FuzzyMatch> {
+    let text: Vec<char> = text.chars().take(MAX_TEXT_CHARS).collect();
+    let (m, n) = (pattern.len(), text.len());
+    if m == 0 || m > n {
+        return None;
+    }
+    let bonus: Vec<i32> = (0..n)
+        .map(|j| char_bonus(j.checked_sub(1).map(|k| text[k]), text[j]))
+        .collect();
+
+    // scores[i][j]: pattern[..=i] 匹配完成且 pattern[i] 落在 text[j] 时的最高分
+    const NONE: i32 = i32::MIN / 2;
+    let mut scores = vec![vec![NONE; n]; m];
+    for i in 0..m {
+        let mut gap_best = NONE; // 之前某处结束、中间隔开至少一个字符时的最高分 (已计入间隔扣分)
+        for j in 0..n {
+            if i > 0 && j >= 2 {
+                gap_best = (gap_best + GAP_EXTENSION).max(scores[i - 1][j - 2] + GAP_START);
+            }
+            if !chars_equal(pattern[i], text[j], case_sensitive) {
+                continue;
+            }
+            scores[i][j] = if i == 0 {
+                SCORE_MATCH + bonus[j] * FIRST_CHAR_MULTIPLIER
+            } else {
+                let consecutive = if j >= 1 { scores[i - 1][j - 1] + BONUS_CONSECUTIVE } else { NONE };
+                let best = consecutive.max(gap_best);
+                if best < NONE / 2 {
+                    continue;
+                }
+                best + SCORE_MATCH + bonus[j]
+            };
+        }
+    }
+
+    // 回溯得到匹配位置
+    let (mut j, score) = scores[m - 1]
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| **s > NONE)
+        .max_by_key(|(j, s)| (**s, std::cmp::Reverse(*j)))
+        .map(|(j, s)| (j, *s))?;
+    let mut positions = vec![j];
+    for i in (1..m).rev() {
+        let base = scores[i][j] - SCORE_MATCH - bonus[j];
+        let previous = if j >= 1 && scores[i - 1][j - 1] + BONUS_CONSECUTIVE == base {
+            j - 1
+        } else {
+            let gap_score = |k: usize| scores[i - 1][k] + GAP_START + GAP_EXTENSION * (j - k - 2) as i32;
+            (0..j.saturating_sub(1))
+                .rev()
+                .find(|&k| scores[i - 1][k] > NONE && gap_score(k) == base)?
+        };
+        positions.push(previous);
+        j = previous;
+    }
+    positions.reverse();
+    Some(FuzzyMatch { score, positions })
+}
+
+/// 字符下标转换为合并后的 UTF-16 范围
+fn utf16_ranges(text: &str, positions: &[usize]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+    let mut wanted = positions.iter().copied().peekable();
+    for (index, c) in text.chars().enumerate() {
+        let width = c.len_utf16();
+        if wanted.peek() == Some(&index) {
+            wanted.next();
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end += width,
+                _ => ranges.push(offset..offset + width),
+            }
+        }
+        offset += width;
+    }
+    ranges.into_iter().map(|r| (r.start, r.end)).collect()
+}
+
+/// 在标题的拼音形式中匹配，匹配位置换算为标题中的字符下标
+/// 拼音只含小写字母数字，因此只匹配由字母数字组成的词，且不区分大小写
+fn pinyin_match(term: &[char], pinyin: &[PinyinForm]) -> Option<FuzzyMatch> {
+    if !term.iter().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let lower: Vec<char> = term.iter().map(|c| c.to_ascii_lowercase()).collect();
+    pinyin
+        .iter()
+        .filter_map(|form| fuzzy_match(&lower, &form.text, false).map(|m| (form, m)))
+        .max_by_key(|(_, m)| m.score)
+        .map(|(form, m)| {
+            let mut positions: Vec<usize> = m.positions.iter().map(|&p| form.char_indices[p]).collect();
+            positions.dedup();
+            FuzzyMatch { score: m.score, positions }
+        })
+}
+
+/// 对一条笔记打分：查询按空格拆分为多个词，每个词都必须在标题 (含拼音) 或路径中匹配
+fn score_note(
+    terms: &[Vec<char>],
+    case_sensitive: bool,
+    path: &str,
+    title: &str,
+    pinyin: &[PinyinForm],
+) -> Option<QuickOpenItem> {
+    let mut score = 0;
+    let mut title_positions: Vec<usize> = Vec::new();
+    let mut path_positions: Vec<usize> = Vec::new();
+    for term in terms {
+        let direct = fuzzy_match(term, title, case_sensitive);
+        let in_title = match (direct, pinyin_match(term, pinyin)) {
+            (Some(d), Some(p)) => Some(if p.score > d.score { p } else { d }),
+            (d, p) => d.or(p),
+        }
+        .map(|mut m| {
+            m.score += BONUS_TITLE;
+            m
+        });
+        let in_path = fuzzy_match(term, path, case_sensitive);
+        match (in_title, in_path) {
+            (Some(t), Some(p)) if t.score >= p.score => {
+                score += t.score;
+                title_positions.extend(t.positions);
+            }
+            (_, Some(p)) => {
+                score += p.score;
+                path_positions.extend(p.positions);
+            }
+            (Some(t), None) => {
+                score += t.score;
+                title_positions.extend(t.positions);
+            }
+            (None, None) => return None,
+        }
+    }
+    title_positions.sort_unstable();
+    title_positions.dedup();
+    path_positions.sort_unstable();
+    path_positions.dedup();
+    Some(QuickOpenItem {
+        path: path.to_string(),
+        title: title.to_string(),
+        score,
+        title_ranges: utf16_ranges(title, &title_positions),
+        path_ranges: utf16_ranges(path, &path_positions),
+    })
+}
+
+/// 对笔记列表 (路径, 标题) 做模糊匹配，按得分从高到低返回
+/// 查询中含大写字母时区分大小写 (smart case)
+pub fn quick_open_matches(notes: Vec<(String, String)>, query: &str, limit: usize) -> Vec<QuickOpenItem> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let terms: Vec<Vec<char>> = query.split_whitespace().map(|t| t.chars().collect()).collect();
+    let any_ascii_term = terms.iter().any(|t| t.iter().all(|c| c.is_ascii_alphanumeric()));
+    let mut items: Vec<QuickOpenItem> = notes
+        .iter()
+        .filter_map(|(path, title)| {
+            let pinyin = if any_ascii_term { pinyin_forms(title) } else { Vec::new() };
+            score_note(&terms, case_sensitive, path, title, &pinyin)
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.path.len().cmp(&b.path.len()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    items.truncate(limit);
+    items
+}
+
+/// 快速打开：按标题和路径模糊查找笔记；查询为空时返回最近修改的笔记
+#[command]
+pub async fn quick_open(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<QuickOpenItem>, String> {
+    let limit = limit.unwrap_or(DEFAULT_QUICK_OPEN_LIMIT).max(1);
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT path, title FROM files WHERE is_dir = 0 ORDER BY updated_at DESC")
+        .map_err(|e| e.to_string())?;
+    let notes: Vec<(String, String)> = stmt
+        .query_map([], |row| {
+            let path: String = row.get(0)?;
+            let title = row.get::<_, Option<String>>(1)?.filter(|t| !t.is_empty()).unwrap_or_else(|| {
+                Path::new(&path)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone())
+            });
+            Ok((path, title))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    if query.trim().is_empty() {
+        return Ok(notes
+            .into_iter()
+            .take(limit)
+            .map(|(path, title)| QuickOpenItem {
+                path,
+                title,
+                score: 0,
+                title_ranges: Vec::new(),
+                path_ranges: Vec::new(),
+            })
+            .collect());
+    }
+    Ok(quick_open_matches(notes, &query, limit))
+}
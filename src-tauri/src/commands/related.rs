@@ -0,0 +1,179 @@
+// src-tauri/src/commands/related.rs
+// 相关笔记推荐：内容相似 (索引 MoreLikeThis)、共同标签、共同链接邻居
+
+use crate::commands::graph::LinkGraph;
+use crate::{search_core, AppState};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tauri::{command, State};
+
+const DEFAULT_RELATED_LIMIT: usize = 10;
+
+/// 各项信号在总分中的权重：内容相似度归一化到 0~1，标签和链接按共同数量计分并封顶
+const CONTENT_WEIGHT: f32 = 0.6;
+const TAG_WEIGHT: f32 = 0.15;
+const LINK_WEIGHT: f32 = 0.1;
+const MAX_TAG_SCORE: f32 = 0.3;
+const MAX_LINK_SCORE: f32 = 0.3;
+
+/// 从索引中取出的候选数 (相对于 limit 的倍数)，留出余量给标签和链接信号重新排序
+const CANDIDATE_FACTOR: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedNote {
+    pub path: String,
+    pub title: String,
+    pub score: f32,
+    pub shared_terms: Vec<String>,
+    pub shared_tags: Vec<String>,
+    pub shared_links: Vec<String>, // 两篇笔记共同链接到 (或被其链接) 的笔记标题
+}
+
+struct Candidate {
+    title: String,
+    content_score: f32,
+    shared_terms: Vec<String>,
+    shared_tags: Vec<String>,
+    shared_links: Vec<i64>,
+}
+
+fn note_tags(conn: &Connection) -> Result<HashMap<i64, HashSet<String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT ft.file_id, t.name FROM file_tags ft INNER JOIN tags t ON t.id = ft.tag_id")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    let mut tags: HashMap<i64, HashSet<String>> = HashMap::new();
+    for row in rows {
+        let (file_id, name) = row.map_err(|e| e.to_string())?;
+        tags.entry(file_id).or_default().insert(name);
+    }
+    Ok(tags)
+}
+
+/// 推荐与当前笔记相关、但尚未从当前笔记链接过去的笔记
+/// 索引未加载时只使用标签和链接信号
+#[command]
+pub async fn get_related_notes(
+    path: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<RelatedNote>, String> {
+    let limit = limit.unwrap_or(DEFAULT_RELATED_LIMIT).max(1);
+
+    let index = state.search_index.lock().unwrap().as_ref().cloned();
+    let similar = match index {
+        Some(index) => search_core::find_similar_notes(&index, &path, limit * CANDIDATE_FACTOR)
+            .map_err(|e| e.to_string())?,
+        None => {
+            println!("⚠️ [related] 索引未加载，仅使用标签和链接推荐");
+            Vec::new()
+        }
+    };
+
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let mut notes: HashMap<i64, (String, String)> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare("SELECT id, path, title FROM files WHERE is_dir = 0")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let path: String = row.get(1)?;
+                let title = row.get::<_, Option<String>>(2)?.unwrap_or_else(|| path.clone());
+                Ok((row.get::<_, i64>(0)?, path, title))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (id, path, title) = row.map_err(|e| e.to_string())?;
+            notes.insert(id, (path, title));
+        }
+    }
+    let source_id: i64 = conn
+        .query_row("SELECT id FROM files WHERE path = ?1", params![path], |row| row.get(0))
+        .map_err(|_| format!("笔记不存在: {}", path))?;
+    let id_by_path: HashMap<&str, i64> = notes.iter().map(|(id, (p, _))| (p.as_str(), *id)).collect();
+
+    let mut candidates: HashMap<i64, Candidate> = HashMap::new();
+    let new_candidate = |title: &str| Candidate {
+        title: title.to_string(),
+        content_score: 0.0,
+        shared_terms: Vec::new(),
+        shared_tags: Vec::new(),
+        shared_links: Vec::new(),
+    };
+
+    // 1. 内容相似 (按最高分归一化)
+    let max_score = similar.iter().map(|s| s.score).fold(0.0_f32, f32::max);
+    for note in similar {
+        let Some(&id) = id_by_path.get(note.path.as_str()) else { continue };
+        let candidate = candidates.entry(id).or_insert_with(|| new_candidate(&note.title));
+        candidate.content_score = if max_score > 0.0 { note.score / max_score } else { 0.0 };
+        candidate.shared_terms = note.shared_terms;
+    }
+
+    // 2. 共同标签
+    let tags = note_tags(&conn)?;
+    if let Some(source_tags) = tags.get(&source_id) {
+        for (&id, other_tags) in &tags {
+            if id == source_id || !notes.contains_key(&id) {
+                continue;
+            }
+            let mut shared: Vec<String> = source_tags.intersection(other_tags).cloned().collect();
+            if shared.is_empty() {
+                continue;
+            }
+            shared.sort();
+            candidates.entry(id).or_insert_with(|| new_candidate(&notes[&id].1)).shared_tags = shared;
+        }
+    }
+
+    // 3. 共同链接邻居 (忽略方向)
+    let links = LinkGraph::load(&conn)?;
+    let source_neighbours: HashSet<i64> = links.neighbours(source_id, false).collect();
+    let mut shared_links: HashMap<i64, HashSet<i64>> = HashMap::new();
+    for &neighbour in &source_neighbours {
+        for other in links.neighbours(neighbour, false) {
+            if other != source_id && notes.contains_key(&other) {
+                shared_links.entry(other).or_default().insert(neighbour);
+            }
+        }
+    }
+    for (id, neighbours) in shared_links {
+        let mut neighbours: Vec<i64> = neighbours.into_iter().collect();
+        neighbours.sort_unstable();
+        candidates.entry(id).or_insert_with(|| new_candidate(&notes[&id].1)).shared_links = neighbours;
+    }
+
+    // 已从当前笔记链接过去的笔记不再推荐
+    let already_linked: HashSet<i64> = links.outgoing.get(&source_id).into_iter().flatten().copied().collect();
+    let mut related: Vec<RelatedNote> = candidates
+        .into_iter()
+        .filter(|(id, _)| *id != source_id && !already_linked.contains(id))
+        .filter_map(|(id, candidate)| {
+            let (note_path, _) = notes.get(&id)?;
+            let score = CONTENT_WEIGHT * candidate.content_score
+                + (TAG_WEIGHT * candidate.shared_tags.len() as f32).min(MAX_TAG_SCORE)
+                + (LINK_WEIGHT * candidate.shared_links.len() as f32).min(MAX_LINK_SCORE);
+            Some(RelatedNote {
+                path: note_path.clone(),
+                title: candidate.title,
+                score,
+                shared_terms: candidate.shared_terms,
+                shared_tags: candidate.shared_tags,
+                shared_links: candidate
+                    .shared_links
+                    .iter()
+                    .filter_map(|n| notes.get(n).map(|(_, title)| title.clone()))
+                    .collect(),
+            })
+        })
+        .collect();
+    related.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    related.truncate(limit);
+    Ok(related)
+}
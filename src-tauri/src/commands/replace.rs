@@ -0,0 +1,503 @@
+// src-tauri/src/commands/replace.rs
+// 全局查找替换：先预览每个笔记的改动 (差异)，确认后通过 save_file 写入，整批记录以便撤销
+
+use crate::commands::fs::save_file;
+use crate::commands::literal_search::build_literal_regex;
+use crate::commands::path_utils::to_absolute_path;
+use crate::commands::query::paths_with_tag;
+use crate::AppState;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::Path;
+use tauri::{command, State};
+
+/// 差异中改动前后保留的上下文行数
+const DIFF_CONTEXT_LINES: usize = 2;
+/// 预览最多列出的笔记数
+const MAX_PREVIEW_FILES: usize = 1000;
+/// 保留的替换批次数 (更早的批次不能再撤销)
+const MAX_REPLACE_BATCHES: i64 = 50;
+
+/// 替换范围：为空表示整个工作区；同时指定时须同时满足
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ReplaceScope {
+    pub folders: Vec<String>, // 只替换这些文件夹 (含子文件夹) 中的笔记
+    pub tags: Vec<String>,    // 只替换带有其中任一标签的笔记
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub kind: &'static str, // "context" | "removed" | "added"
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    pub old_start: usize, // 从 1 开始
+    pub new_start: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReplacePreview {
+    pub path: String,
+    pub title: String,
+    pub replacements: usize,
+    pub hash: String, // 预览时的内容指纹，应用时用于确认笔记未被修改
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplacePreview {
+    pub files: Vec<FileReplacePreview>,
+    pub total_replacements: usize,
+    pub truncated: bool, // 超过 MAX_PREVIEW_FILES，只列出了前面的笔记
+}
+
+/// 用户确认要替换的笔记及预览时的内容指纹
+#[derive(Debug, Clone, Deserialize)]
+pub struct AcceptedFile {
+    pub path: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplyReplaceResult {
+    pub batch_id: Option<i64>, // 没有任何笔记被修改时为 None
+    pub replaced_files: Vec<String>,
+    pub replacements: usize,
+    pub skipped: Vec<SkippedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoReplaceResult {
+    pub restored: Vec<String>,
+    pub conflicts: Vec<String>, // 替换后又被修改过的笔记，未撤销
+    pub failed: Vec<SkippedFile>, // 恢复时保存失败的笔记
+    pub undone: bool,             // 至少恢复了一个笔记，批次已标记为撤销；否则解决冲突后可重试
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplaceBatch {
+    pub id: i64,
+    pub pattern: String,
+    pub replacement: String,
+    pub is_regex: bool,
+    pub case_sensitive: bool,
+    pub created_at: String,
+    pub undone: bool,
+    pub files: usize,
+}
+
+/// 一处替换：(原文中的字节范围, 替换后的文本)
+type Edit = (Range<usize>, String);
+
+fn content_hash(content: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// 计算所有替换，跳过替换前后相同的匹配
+/// 正则模式下替换文本中的 $1、${name} 引用捕获组
+fn replacement_edits(content: &str, regex: &Regex, replacement: &str, is_regex: bool) -> Vec<Edit> {
+    regex
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let m = caps.get(0)?;
+            let text = if is_regex {
+                let mut expanded = String::new();
+                caps.expand(replacement, &mut expanded);
+                expanded
+            } else {
+                replacement.to_string()
+            };
+            (text != m.as_str()).then(|| (m.range(), text))
+        })
+        .collect()
+}
+
+fn apply_edits(content: &str, edits: &[Edit]) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, text) in edits {
+        output.push_str(&content[last..range.start]);
+        output.push_str(text);
+        last = range.end;
+    }
+    output.push_str(&content[last..]);
+    output
+}
+
+/// 连续改动的若干行
+struct ChangedLines {
+    first: usize, // 原文中的行号 (从 0 开始)
+    last: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+}
+
+/// 按行生成差异：相邻或重叠的改动合并，改动之间相隔不超过 2 倍上下文时合并到同一个块
+fn diff_hunks(content: &str, edits: &[Edit]) -> Vec<DiffHunk> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&s| s <= offset) - 1;
+    let line_end = |line: usize| line_starts.get(line + 1).map(|s| s - 1).unwrap_or(content.len());
+    let line_text = |line: usize| content[line_starts[line]..line_end(line)].trim_end_matches('\r').to_string();
+
+    // 1. 把改动按所在行分组
+    let mut groups: Vec<(usize, usize, Vec<&Edit>)> = Vec::new();
+    for edit in edits {
+        let first = line_of(edit.0.start);
+        let last = line_of(edit.0.end.max(edit.0.start + 1) - 1).max(first);
+        match groups.last_mut() {
+            Some((_, group_last, members)) if first <= *group_last + 1 => {
+                *group_last = (*group_last).max(last);
+                members.push(edit);
+            }
+            _ => groups.push((first, last, vec![edit])),
+        }
+    }
+    let changes: Vec<ChangedLines> = groups
+        .into_iter()
+        .map(|(first, last, members)| {
+            let region_start = line_starts[first];
+            let region = &content[region_start..line_end(last)];
+            let shifted: Vec<Edit> = members
+                .into_iter()
+                .map(|(range, text)| (range.start - region_start..range.end - region_start, text.clone()))
+                .collect();
+            let new_region = apply_edits(region, &shifted);
+            ChangedLines {
+                first,
+                last,
+                old_lines: region.split('\n').map(|l| l.trim_end_matches('\r').to_string()).collect(),
+                new_lines: new_region.split('\n').map(|l| l.trim_end_matches('\r').to_string()).collect(),
+            }
+        })
+        .collect();
+
+    // 2. 加上下文组成差异块
+    let context = |line: usize| DiffLine { kind: "context", text: line_text(line) };
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+    let mut current: Option<DiffHunk> = None;
+    let mut previous_last = 0;
+    let mut line_delta: isize = 0; // 之前的改动造成的行数变化
+    for change in changes {
+        if let Some(mut hunk) = current.take() {
+            if change.first <= previous_last + 2 * DIFF_CONTEXT_LINES + 1 {
+                hunk.lines.extend((previous_last + 1..change.first).map(context));
+                current = Some(hunk);
+            } else {
+                let after_end = (previous_last + DIFF_CONTEXT_LINES).min(line_starts.len() - 1);
+                hunk.lines.extend((previous_last + 1..=after_end).map(context));
+                hunks.push(hunk);
+            }
+        }
+        let hunk = current.get_or_insert_with(|| {
+            let start = change.first.saturating_sub(DIFF_CONTEXT_LINES);
+            DiffHunk {
+                old_start: start + 1,
+                new_start: (start as isize + line_delta) as usize + 1,
+                lines: (start..change.first).map(context).collect(),
+            }
+        });
+        hunk.lines.extend(change.old_lines.iter().map(|t| DiffLine { kind: "removed", text: t.clone() }));
+        hunk.lines.extend(change.new_lines.iter().map(|t| DiffLine { kind: "added", text: t.clone() }));
+        line_delta += change.new_lines.len() as isize - change.old_lines.len() as isize;
+        previous_last = change.last;
+    }
+    if let Some(mut hunk) = current {
+        let after_end = (previous_last + DIFF_CONTEXT_LINES).min(line_starts.len() - 1);
+        hunk.lines.extend((previous_last + 1..=after_end).map(context));
+        hunks.push(hunk);
+    }
+    hunks
+}
+
+/// 范围内的笔记 (路径, 标题)
+fn scoped_notes(conn: &Connection, scope: &ReplaceScope) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, COALESCE(title, '') FROM files WHERE is_dir = 0 ORDER BY path")
+        .map_err(|e| e.to_string())?;
+    let mut notes: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let folders: Vec<String> = scope
+        .folders
+        .iter()
+        .map(|f| f.trim().trim_matches('/').replace('\\', "/"))
+        .filter(|f| !f.is_empty())
+        .collect();
+    if !folders.is_empty() {
+        notes.retain(|(path, _)| folders.iter().any(|f| path.starts_with(&format!("{}/", f))));
+    }
+    if !scope.tags.is_empty() {
+        let mut tagged: HashSet<String> = HashSet::new();
+        for tag in &scope.tags {
+            tagged.extend(paths_with_tag(conn, tag)?);
+        }
+        notes.retain(|(path, _)| tagged.contains(path));
+    }
+    Ok(notes)
+}
+
+/// 逐个读取笔记 (路径, 标题)，列出会被修改的笔记及其差异
+fn preview_notes(
+    root_path: &str,
+    notes: Vec<(String, String)>,
+    regex: &Regex,
+    replacement: &str,
+    is_regex: bool,
+) -> ReplacePreview {
+    let mut preview = ReplacePreview { files: Vec::new(), total_replacements: 0, truncated: false };
+    for (path, title) in notes {
+        let absolute_path = to_absolute_path(Path::new(root_path), Path::new(&path));
+        let Ok(content) = std::fs::read_to_string(&absolute_path) else { continue };
+        let edits = replacement_edits(&content, regex, replacement, is_regex);
+        if edits.is_empty() {
+            continue;
+        }
+        if preview.files.len() >= MAX_PREVIEW_FILES {
+            preview.truncated = true;
+            break;
+        }
+        preview.total_replacements += edits.len();
+        preview.files.push(FileReplacePreview {
+            title: if title.is_empty() { path.clone() } else { title },
+            path,
+            replacements: edits.len(),
+            hash: content_hash(&content),
+            hunks: diff_hunks(&content, &edits),
+        });
+    }
+    preview
+}
+
+/// 预览替换：列出每个会被修改的笔记及其差异
+#[command]
+pub async fn preview_replace(
+    root_path: String,
+    pattern: String,
+    replacement: String,
+    is_regex: bool,
+    case_sensitive: bool,
+    scope: Option<ReplaceScope>,
+    state: State<'_, AppState>,
+) -> Result<ReplacePreview, String> {
+    let regex = build_literal_regex(&pattern, is_regex, case_sensitive)?;
+    let notes = {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        scoped_notes(&conn, &scope.unwrap_or_default())?
+    };
+
+    // 读取所有笔记较慢，放到阻塞线程池中执行，不占用异步运行时
+    let preview = {
+        let replacement = replacement.clone();
+        tauri::async_runtime::spawn_blocking(move || preview_notes(&root_path, notes, &regex, &replacement, is_regex))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    println!(
+        "🔁 [replace] 预览 '{}' -> '{}': {} 个笔记, {} 处",
+        pattern,
+        replacement,
+        preview.files.len(),
+        preview.total_replacements
+    );
+    Ok(preview)
+}
+
+/// 对确认的笔记执行替换，每个笔记都通过 save_file 保存 (加锁、更新链接、历史和索引)
+/// 笔记在预览后被修改过时跳过；成功替换的笔记记录为一个批次，可用 undo_replace 撤销
+#[command]
+pub async fn apply_replace(
+    root_path: String,
+    pattern: String,
+    replacement: String,
+    is_regex: bool,
+    case_sensitive: bool,
+    files: Vec<AcceptedFile>,
+    state: State<'_, AppState>,
+) -> Result<ApplyReplaceResult, String> {
+    let regex = build_literal_regex(&pattern, is_regex, case_sensitive)?;
+    let mut result = ApplyReplaceResult { batch_id: None, replaced_files: Vec::new(), replacements: 0, skipped: Vec::new() };
+    let mut changed: Vec<(String, String, String)> = Vec::new(); // (路径, 原内容, 新内容)
+    let skip = |path: &str, reason: String| SkippedFile { path: path.to_string(), reason };
+
+    for file in files {
+        let absolute_path = to_absolute_path(Path::new(&root_path), Path::new(&file.path));
+        let content = match std::fs::read_to_string(&absolute_path) {
+            Ok(c) => c,
+            Err(e) => {
+                result.skipped.push(skip(&file.path, format!("读取失败: {}", e)));
+                continue;
+            }
+        };
+        if content_hash(&content) != file.hash {
+            result.skipped.push(skip(&file.path, "预览后笔记已被修改，请重新预览".to_string()));
+            continue;
+        }
+        let edits = replacement_edits(&content, &regex, &replacement, is_regex);
+        if edits.is_empty() {
+            continue;
+        }
+        let new_content = apply_edits(&content, &edits);
+        if let Err(e) = save_file(root_path.clone(), file.path.clone(), new_content.clone(), state.clone()).await {
+            result.skipped.push(skip(&file.path, format!("保存失败: {}", e)));
+            continue;
+        }
+        result.replacements += edits.len();
+        result.replaced_files.push(file.path.clone());
+        changed.push((file.path, content, new_content));
+    }
+
+    if !changed.is_empty() {
+        let db_pool = state.db_pool.lock().unwrap();
+        let mut conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO replace_batches (pattern, replacement, is_regex, case_sensitive) VALUES (?1, ?2, ?3, ?4)",
+            params![pattern, replacement, is_regex, case_sensitive],
+        )
+        .map_err(|e| e.to_string())?;
+        let batch_id = tx.last_insert_rowid();
+        for (path, old_content, new_content) in &changed {
+            tx.execute(
+                "INSERT INTO replace_batch_files (batch_id, path, old_content, new_content) VALUES (?1, ?2, ?3, ?4)",
+                params![batch_id, path, old_content, new_content],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "DELETE FROM replace_batches WHERE id NOT IN (SELECT id FROM replace_batches ORDER BY id DESC LIMIT ?1)",
+            params![MAX_REPLACE_BATCHES],
+        )
+        .map_err(|e| e.to_string())?;
+        // 显式删除被淘汰批次的文件内容，不依赖 ON DELETE CASCADE (需要开启 foreign_keys)
+        tx.execute(
+            "DELETE FROM replace_batch_files WHERE batch_id NOT IN (SELECT id FROM replace_batches)",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        result.batch_id = Some(batch_id);
+    }
+    println!(
+        "🔁 [replace] 已替换 {} 个笔记 ({} 处)，跳过 {} 个",
+        result.replaced_files.len(),
+        result.replacements,
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+/// 撤销一次替换：恢复批次中的笔记；替换后又被修改过的笔记不会被覆盖
+/// 没有任何笔记被恢复时批次保持未撤销，解决冲突后可以再次撤销
+#[command]
+pub async fn undo_replace(root_path: String, batch_id: i64, state: State<'_, AppState>) -> Result<UndoReplaceResult, String> {
+    let files: Vec<(String, String, String)> = {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        let undone: Option<Option<String>> = conn
+            .query_row("SELECT undone_at FROM replace_batches WHERE id = ?1", params![batch_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        match undone {
+            None => return Err(format!("替换记录不存在: {}", batch_id)),
+            Some(Some(_)) => return Err("该替换已撤销".to_string()),
+            Some(None) => {}
+        }
+        let mut stmt = conn
+            .prepare("SELECT path, old_content, new_content FROM replace_batch_files WHERE batch_id = ?1 ORDER BY path")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![batch_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    let mut result = UndoReplaceResult { restored: Vec::new(), conflicts: Vec::new(), failed: Vec::new(), undone: false };
+    for (path, old_content, new_content) in files {
+        let absolute_path = to_absolute_path(Path::new(&root_path), Path::new(&path));
+        let current = std::fs::read_to_string(&absolute_path).ok();
+        if current.as_deref() != Some(new_content.as_str()) {
+            result.conflicts.push(path);
+            continue;
+        }
+        if let Err(e) = save_file(root_path.clone(), path.clone(), old_content, state.clone()).await {
+            result.failed.push(SkippedFile { path, reason: format!("保存失败: {}", e) });
+            continue;
+        }
+        result.restored.push(path);
+    }
+
+    if !result.restored.is_empty() {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE replace_batches SET undone_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![batch_id],
+        )
+        .map_err(|e| e.to_string())?;
+        result.undone = true;
+    }
+    println!(
+        "↩️ [replace] 撤销批次 {}: 恢复 {} 个笔记，{} 个有冲突，{} 个失败",
+        batch_id,
+        result.restored.len(),
+        result.conflicts.len(),
+        result.failed.len()
+    );
+    Ok(result)
+}
+
+/// 最近的替换批次 (新的在前)
+#[command]
+pub async fn list_replace_batches(state: State<'_, AppState>) -> Result<Vec<ReplaceBatch>, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.pattern, b.replacement, b.is_regex, b.case_sensitive, b.created_at, b.undone_at,
+                    (SELECT COUNT(*) FROM replace_batch_files f WHERE f.batch_id = b.id)
+             FROM replace_batches b ORDER BY b.id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let batches = stmt
+        .query_map([], |row| {
+            Ok(ReplaceBatch {
+                id: row.get(0)?,
+                pattern: row.get(1)?,
+                replacement: row.get(2)?,
+                is_regex: row.get(3)?,
+                case_sensitive: row.get(4)?,
+                created_at: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                undone: row.get::<_, Option<String>>(6)?.is_some(),
+                files: row.get::<_, i64>(7)? as usize,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(batches)
+}
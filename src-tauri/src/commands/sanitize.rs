@@ -0,0 +1,133 @@
+// src-tauri/src/commands/sanitize.rs
+// 渲染结果的 HTML 安全过滤 (白名单可配置)
+
+use crate::database::{get_setting, set_setting};
+use crate::AppState;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{command, State};
+
+const SETTING_KEY: &str = "render.sanitize";
+
+/// 内容会被整体移除的标签，不能出现在白名单中
+const FORBIDDEN_TAGS: &[&str] = &["script", "style"];
+
+/// 渲染器自身输出所需的属性 (锚点、内部链接、任务列表、表格对齐)
+const BUILTIN_GENERIC_ATTRIBUTES: &[&str] = &["class", "id"];
+const BUILTIN_STYLE_PROPERTIES: &[&str] = &["text-align"];
+
+/// 在默认安全白名单基础上追加的标签、属性和 URL 协议
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SanitizeConfig {
+    pub enabled: bool,
+    pub extra_tags: Vec<String>,
+    pub extra_tag_attributes: HashMap<String, Vec<String>>,
+    pub extra_generic_attributes: Vec<String>,
+    pub extra_url_schemes: Vec<String>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        SanitizeConfig {
+            enabled: true,
+            extra_tags: Vec::new(),
+            extra_tag_attributes: HashMap::new(),
+            extra_generic_attributes: Vec::new(),
+            extra_url_schemes: vec!["asset".to_string()],
+        }
+    }
+}
+
+impl SanitizeConfig {
+    /// 拒绝会导致脚本执行或与过滤器冲突的配置项
+    fn validate(&self) -> Result<(), String> {
+        for tag in self.extra_tags.iter().chain(self.extra_tag_attributes.keys()) {
+            if FORBIDDEN_TAGS.contains(&tag.trim().to_lowercase().as_str()) {
+                return Err(format!("不允许加入白名单的标签: {}", tag));
+            }
+        }
+        let attributes = self
+            .extra_generic_attributes
+            .iter()
+            .chain(self.extra_tag_attributes.values().flatten());
+        for attr in attributes {
+            let attr = attr.trim().to_lowercase();
+            if attr.starts_with("on") || attr == "rel" {
+                return Err(format!("不允许加入白名单的属性: {}", attr));
+            }
+        }
+        for scheme in &self.extra_url_schemes {
+            if scheme.trim().eq_ignore_ascii_case("javascript") {
+                return Err("不允许 javascript: 协议".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn load_sanitize_config(conn: &Connection) -> SanitizeConfig {
+    get_setting(conn, SETTING_KEY).unwrap_or_default()
+}
+
+/// 过滤渲染后的 HTML：移除脚本、事件属性和不在白名单中的标签/属性
+pub fn sanitize_html(html: &str, config: &SanitizeConfig) -> String {
+    if !config.enabled {
+        return html.to_string();
+    }
+    // 数据库中的配置可能被手动修改过，非法项直接忽略，避免 ammonia 断言失败
+    if let Err(e) = config.validate() {
+        eprintln!("⚠️ [sanitize] 白名单配置无效，使用默认配置: {}", e);
+        return sanitize_html(html, &SanitizeConfig::default());
+    }
+
+    let extra_tags: Vec<String> = config.extra_tags.iter().map(|t| t.trim().to_lowercase()).collect();
+    let extra_tag_attributes: Vec<(String, Vec<String>)> = config
+        .extra_tag_attributes
+        .iter()
+        .map(|(tag, attrs)| {
+            (
+                tag.trim().to_lowercase(),
+                attrs.iter().map(|a| a.trim().to_lowercase()).collect(),
+            )
+        })
+        .collect();
+    let extra_generic: Vec<String> = config
+        .extra_generic_attributes
+        .iter()
+        .map(|a| a.trim().to_lowercase())
+        .collect();
+
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .add_tag_attributes("th", &["style"])
+        .add_tag_attributes("td", &["style"])
+        .filter_style_properties(BUILTIN_STYLE_PROPERTIES.iter().copied().collect::<HashSet<_>>())
+        .add_generic_attributes(BUILTIN_GENERIC_ATTRIBUTES)
+        .add_generic_attribute_prefixes(&["data-"])
+        .add_tags(extra_tags.iter().map(String::as_str))
+        .add_generic_attributes(extra_generic.iter().map(String::as_str))
+        .add_url_schemes(config.extra_url_schemes.iter().map(|s| s.trim()));
+    for (tag, attrs) in &extra_tag_attributes {
+        builder.add_tag_attributes(tag.as_str(), attrs.iter().map(String::as_str));
+    }
+    builder.clean(html).to_string()
+}
+
+#[command]
+pub async fn get_sanitize_config(state: State<'_, AppState>) -> Result<SanitizeConfig, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    Ok(load_sanitize_config(&conn))
+}
+
+#[command]
+pub async fn set_sanitize_config(config: SanitizeConfig, state: State<'_, AppState>) -> Result<(), String> {
+    config.validate()?;
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    set_setting(&conn, SETTING_KEY, &config).map_err(|e| e.to_string())
+}
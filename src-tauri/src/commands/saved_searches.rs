@@ -0,0 +1,215 @@
+// src-tauri/src/commands/saved_searches.rs
+// 保存的搜索 (智能文件夹)：搜索语句加筛选条件，显示在侧边栏中，与置顶、收藏的笔记并列
+
+use crate::commands::search::load_ranking_config;
+use crate::search_query::parse_search_query;
+use crate::{search_core, AppState};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+
+/// 执行保存的搜索时最多返回的笔记数
+const MAX_SAVED_SEARCH_RESULTS: usize = 200;
+
+/// 筛选条件，与搜索语句同时满足；同一项中的多个值满足任一即可
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSearchFilters {
+    pub folders: Vec<String>,     // 文件夹 (含子文件夹)
+    pub tags: Vec<String>,        // 标签 (含子标签)
+    pub modified: Option<String>, // 修改时间，写法同搜索语法中的 modified:，如 7d、2024-03
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub filters: SavedSearchFilters,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSearchResults {
+    pub search: SavedSearch,
+    pub effective_query: String, // 搜索语句与筛选条件合并后实际执行的查询
+    pub results: Vec<search_core::SearchResult>,
+}
+
+/// 去掉会破坏短语的引号
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', ""))
+}
+
+/// 把筛选条件合并进搜索语句 (使用 path: / tag: / modified: 语法)
+pub fn effective_query(query: &str, filters: &SavedSearchFilters) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if !query.trim().is_empty() {
+        parts.push(format!("({})", query.trim()));
+    }
+    let folders: Vec<String> = filters
+        .folders
+        .iter()
+        .map(|f| f.trim().trim_matches('/').replace('\\', "/"))
+        .filter(|f| !f.is_empty())
+        .map(|f| format!("path:{}", quote(&format!("{}/*", f))))
+        .collect();
+    if !folders.is_empty() {
+        parts.push(format!("({})", folders.join(" OR ")));
+    }
+    let tags: Vec<String> = filters
+        .tags
+        .iter()
+        .map(|t| t.trim().trim_start_matches('#'))
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("tag:{}", quote(t)))
+        .collect();
+    if !tags.is_empty() {
+        parts.push(format!("({})", tags.join(" OR ")));
+    }
+    if let Some(modified) = filters.modified.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        parts.push(format!("modified:{}", quote(modified)));
+    }
+    parts.join(" ")
+}
+
+/// 检查名称与条件，返回去掉首尾空白的名称
+fn validate(name: &str, query: &str, filters: &SavedSearchFilters) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("名称不能为空".to_string());
+    }
+    let effective = effective_query(query, filters);
+    if effective.is_empty() {
+        return Err("搜索内容和筛选条件不能都为空".to_string());
+    }
+    parse_search_query(&effective).map_err(|e| e.to_string())?;
+    Ok(name.to_string())
+}
+
+fn load_saved_search(conn: &Connection, id: i64) -> Result<SavedSearch, String> {
+    conn.query_row(
+        "SELECT id, name, query, filters, created_at, updated_at FROM saved_searches WHERE id = ?1",
+        params![id],
+        saved_search_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("保存的搜索不存在: {}", id))
+}
+
+fn saved_search_from_row(row: &rusqlite::Row) -> rusqlite::Result<SavedSearch> {
+    let filters: String = row.get(3)?;
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        filters: serde_json::from_str(&filters).unwrap_or_default(),
+        created_at: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+        updated_at: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+    })
+}
+
+/// 名称唯一约束冲突时给出可读的错误
+fn map_write_error(e: rusqlite::Error, name: &str) -> String {
+    match e {
+        rusqlite::Error::SqliteFailure(ref err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+            format!("已存在同名的保存搜索: {}", name)
+        }
+        e => e.to_string(),
+    }
+}
+
+#[command]
+pub async fn create_saved_search(
+    name: String,
+    query: String,
+    filters: Option<SavedSearchFilters>,
+    state: State<'_, AppState>,
+) -> Result<SavedSearch, String> {
+    let filters = filters.unwrap_or_default();
+    let name = validate(&name, &query, &filters)?;
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    let filters_json = serde_json::to_string(&filters).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO saved_searches (name, query, filters) VALUES (?1, ?2, ?3)",
+        params![name, query.trim(), filters_json],
+    )
+    .map_err(|e| map_write_error(e, &name))?;
+    println!("🔖 [saved-search] 已保存: {}", name);
+    load_saved_search(&conn, conn.last_insert_rowid())
+}
+
+/// 所有保存的搜索，按名称排序
+#[command]
+pub async fn list_saved_searches(state: State<'_, AppState>) -> Result<Vec<SavedSearch>, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT id, name, query, filters, created_at, updated_at FROM saved_searches ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let searches = stmt
+        .query_map([], saved_search_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(searches)
+}
+
+#[command]
+pub async fn update_saved_search(
+    id: i64,
+    name: String,
+    query: String,
+    filters: Option<SavedSearchFilters>,
+    state: State<'_, AppState>,
+) -> Result<SavedSearch, String> {
+    let filters = filters.unwrap_or_default();
+    let name = validate(&name, &query, &filters)?;
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    let filters_json = serde_json::to_string(&filters).map_err(|e| e.to_string())?;
+    let updated = conn
+        .execute(
+            "UPDATE saved_searches SET name = ?1, query = ?2, filters = ?3, updated_at = CURRENT_TIMESTAMP WHERE id = ?4",
+            params![name, query.trim(), filters_json, id],
+        )
+        .map_err(|e| map_write_error(e, &name))?;
+    if updated == 0 {
+        return Err(format!("保存的搜索不存在: {}", id));
+    }
+    load_saved_search(&conn, id)
+}
+
+#[command]
+pub async fn delete_saved_search(id: i64, state: State<'_, AppState>) -> Result<(), String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM saved_searches WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 执行保存的搜索，返回智能文件夹中的笔记 (按搜索排序)
+#[command]
+pub async fn execute_saved_search(
+    id: i64,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<SavedSearchResults, String> {
+    let search_index_lock = state.search_index.lock().unwrap();
+    let index = search_index_lock.as_ref().ok_or("索引未初始化")?;
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let search = load_saved_search(&conn, id)?;
+    let effective_query = effective_query(&search.query, &search.filters);
+    let limit = limit.unwrap_or(MAX_SAVED_SEARCH_RESULTS).clamp(1, MAX_SAVED_SEARCH_RESULTS);
+    let ranking = load_ranking_config(&conn);
+    // 智能文件夹只列出真正满足条件的笔记，不做容错匹配
+    let results = search_core::search_top(index, &effective_query, Some(&conn), &ranking, limit, false)
+        .map_err(|e| e.to_string())?;
+    Ok(SavedSearchResults { search, effective_query, results })
+}
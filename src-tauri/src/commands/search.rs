@@ -1,13 +1,42 @@
 // src-tauri/src/commands/search.rs
 // ▼▼▼【核心修改】在这里 ▼▼▼
 use crate::{search_core, AppState}; // 将 `search` 修改为 `search_core`
+use crate::commands::workspace::{mark_all_unindexed, search_index_dir};
+use crate::database::{get_setting, set_setting};
+use crate::search_core::{AnalyzerConfig, RankingConfig, SearchExplanation};
+use crate::search_query::{parse_search_query, SearchQueryError};
+use rusqlite::Connection;
+use serde::Serialize;
 use std::path::Path;
 use tauri::{command, State};
 
+const ANALYZER_SETTING_KEY: &str = "search.analyzer";
+const RANKING_SETTING_KEY: &str = "search.ranking";
+
+pub fn load_analyzer_config(conn: &Connection) -> AnalyzerConfig {
+    get_setting(conn, ANALYZER_SETTING_KEY).unwrap_or_default()
+}
+
+pub fn load_ranking_config(conn: &Connection) -> RankingConfig {
+    get_setting(conn, RANKING_SETTING_KEY).unwrap_or_default()
+}
+
+/// 当前工作区的分词设置，数据库未初始化时使用默认设置
+fn current_analyzer_config(state: &State<'_, AppState>) -> AnalyzerConfig {
+    let db_pool = state.db_pool.lock().unwrap();
+    db_pool
+        .as_ref()
+        .and_then(|pool| pool.get().ok())
+        .map(|conn| load_analyzer_config(&conn))
+        .unwrap_or_default()
+}
+
 #[command]
 pub async fn initialize_index_command(root_path: String, state: State<'_, AppState>) -> Result<(), String> {
     // 将 `search::` 修改为 `search_core::`
-    let index = search_core::initialize_index(Path::new(&root_path)).map_err(|e| e.to_string())?;
+    let analyzer_config = current_analyzer_config(&state);
+    let (index, _) =
+        search_core::initialize_index(Path::new(&root_path), &analyzer_config).map_err(|e| e.to_string())?;
     *state.search_index.lock().unwrap() = Some(index);
     Ok(())
 }
@@ -29,15 +58,129 @@ pub async fn index_files(root_path: String, state: State<'_, AppState>) -> Resul
 pub async fn search_notes(query: String, state: State<'_, AppState>) -> Result<Vec<search_core::SearchResult>, String> {
     let search_index_lock = state.search_index.lock().unwrap();
     if let Some(index) = search_index_lock.as_ref() {
+        let db_pool = state.db_pool.lock().unwrap();
+        let conn = db_pool.as_ref().and_then(|pool| pool.get().ok());
+        let ranking = conn.as_deref().map(load_ranking_config).unwrap_or_default();
         // 将 `search::` 修改为 `search_core::`
-        search_core::search(index, &query).map_err(|e| e.to_string())
+        search_core::search(index, &query, conn.as_deref(), &ranking).map_err(|e| e.to_string())
     } else {
         Err("索引未初始化".to_string())
     }
 }
 
+/// 解释某个笔记在搜索结果中的得分 (文本相关度与各项排序加权)，笔记不匹配查询时返回 None
+#[command]
+pub async fn explain_search_result(
+    query: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Option<SearchExplanation>, String> {
+    let search_index_lock = state.search_index.lock().unwrap();
+    let index = search_index_lock.as_ref().ok_or("索引未初始化")?;
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().and_then(|pool| pool.get().ok());
+    let ranking = conn.as_deref().map(load_ranking_config).unwrap_or_default();
+    search_core::explain_search(index, &query, &path, conn.as_deref(), &ranking).map_err(|e| e.to_string())
+}
+
+/// 检查搜索语法，返回第一个错误的位置和说明 (无错误时返回 None)，用于在输入框中提示
+#[command]
+pub async fn check_search_query(query: String) -> Result<Option<SearchQueryError>, String> {
+    if query.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(parse_search_query(&query).err())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TitleMatch {
+    pub path: String,
+    pub title: String,
+    pub score: f32,
+}
+
+/// 基于索引按标题 (含 frontmatter 别名) 搜索笔记，支持拼音全拼和首字母，如 "kfrz" 匹配 "开发日志"
+/// 用于只需要按分词匹配标题的场景 (如链接补全)；快速打开面板使用 quick_open::quick_open
+#[command]
+pub async fn search_note_titles(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TitleMatch>, String> {
+    let search_index_lock = state.search_index.lock().unwrap();
+    let index = search_index_lock.as_ref().ok_or("索引未初始化")?;
+    let matches = search_core::search_titles(index, &query, limit.unwrap_or(20))
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(path, title, score)| TitleMatch { path, title, score })
+        .collect();
+    Ok(matches)
+}
+
+#[command]
+pub async fn get_search_analyzer_config(state: State<'_, AppState>) -> Result<AnalyzerConfig, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    Ok(load_analyzer_config(&conn))
+}
+
+/// 保存分词设置；配置变化时立即清空索引并在后台重新索引
+#[command]
+pub async fn set_search_analyzer_config(config: AnalyzerConfig, state: State<'_, AppState>) -> Result<(), String> {
+    config.validate()?;
+    let db_pool = state.db_pool.lock().unwrap().as_ref().cloned().ok_or("数据库未初始化")?;
+    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    set_setting(&conn, ANALYZER_SETTING_KEY, &config).map_err(|e| e.to_string())?;
+
+    let index = state.search_index.lock().unwrap().as_ref().cloned();
+    let workspace_path = state.current_path.lock().unwrap().clone();
+    let (Some(index), Some(workspace_path)) = (index, workspace_path) else {
+        return Ok(());
+    };
+    let index_path = search_core::index_path(&search_index_dir(Path::new(&workspace_path)));
+    let cleared =
+        search_core::apply_analyzer_config(&index, &index_path, &config).map_err(|e| e.to_string())?;
+    if !cleared {
+        return Ok(());
+    }
+
+    // 先标记为未索引，重新索引中途退出时由下次同步补全；完成后再标记为已索引，避免下次同步重复索引
+    mark_all_unindexed(&db_pool)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        println!("🔀 分词设置已变化，后台重新索引...");
+        let result = search_core::index_documents(&index, &db_pool, Path::new(&workspace_path))
+            .and_then(|()| {
+                let conn = db_pool.get()?;
+                conn.execute("UPDATE files SET indexed = 1 WHERE is_dir = 0 AND indexed = 0", [])?;
+                Ok(())
+            });
+        match result {
+            Ok(()) => println!("✅ 重新索引完成"),
+            Err(e) => eprintln!("❌ 重新索引失败: {}", e),
+        }
+    });
+    Ok(())
+}
+
+#[command]
+pub async fn get_search_ranking_config(state: State<'_, AppState>) -> Result<RankingConfig, String> {
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    Ok(load_ranking_config(&conn))
+}
+
+/// 保存排序设置，下一次搜索即生效 (排序在搜索时计算，不需要重建索引)
+#[command]
+pub async fn set_search_ranking_config(config: RankingConfig, state: State<'_, AppState>) -> Result<(), String> {
+    config.validate()?;
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+    set_setting(&conn, RANKING_SETTING_KEY, &config).map_err(|e| e.to_string())
+}
+
 #[command]
 pub async fn ensure_index_is_loaded(root_path: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let analyzer_config = current_analyzer_config(&state);
     let mut search_index_lock = state.search_index.lock().unwrap();
     if search_index_lock.is_some() {
         return Ok(true);
@@ -45,7 +188,7 @@ pub async fn ensure_index_is_loaded(root_path: String, state: State<'_, AppState
     
     println!("索引未加载，正在初始化...");
     // 将 `search::` 修改为 `search_core::`
-    let index = search_core::initialize_index(Path::new(&root_path))
+    let (index, _) = search_core::initialize_index(Path::new(&root_path), &analyzer_config)
         .map_err(|e| format!("初始化索引失败: {}", e))?;
     *search_index_lock = Some(index);
     
@@ -0,0 +1,248 @@
+// src-tauri/src/commands/special_blocks.rs
+// 数学公式 ($...$ / $$...$$) 与 ```mermaid 图表块
+// 渲染前替换为占位符，避免被 Markdown 解析 (下划线、星号、[[ ]] 等) 和 HTML 过滤破坏，
+// 过滤完成后再还原为供前端 KaTeX / Mermaid 渲染的元素。
+// 搜索索引使用原始 Markdown，公式与图表源码保持可搜索。
+
+use crate::commands::utils::escape_html;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// 占位符使用 Unicode 私有区字符包裹序号；笔记中原有的这两个字符会先被去掉 (见 protect_special_blocks)，
+/// 保证还原时只会展开生成的占位符
+const PLACEHOLDER_START: char = '\u{E000}';
+const PLACEHOLDER_END: char = '\u{E001}';
+
+static STANDALONE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<p>\u{E000}(\d+)\u{E001}</p>\n?").unwrap());
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\u{E000}(\d+)\u{E001}").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    InlineMath,
+    DisplayMath,
+    Mermaid,
+    Html,
+}
+
+#[derive(Debug, Default)]
+pub struct ProtectedBlocks {
+    blocks: Vec<(BlockKind, String)>,
+}
+
+impl ProtectedBlocks {
+    fn push(&mut self, kind: BlockKind, source: &str) -> String {
+        self.blocks.push((kind, source.to_string()));
+        format!("{}{}{}", PLACEHOLDER_START, self.blocks.len() - 1, PLACEHOLDER_END)
+    }
+
+    /// 已渲染并过滤过的 HTML 片段 (如嵌入的笔记)，还原时原样输出
+    pub fn push_html(&mut self, html: &str) -> String {
+        self.push(BlockKind::Html, html)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// standalone 为 true 表示占位符独占一个段落，此时输出块级元素
+    fn render(&self, index: usize, standalone: bool) -> Option<String> {
+        let (kind, source) = self.blocks.get(index)?;
+        Some(match (kind, standalone) {
+            (BlockKind::Html, _) => source.clone(),
+            (BlockKind::Mermaid, _) => format!("<div class=\"mermaid\">{}</div>\n", escape_html(source)),
+            (BlockKind::DisplayMath, true) => {
+                format!("<div class=\"math math-display\">{}</div>\n", escape_html(source))
+            }
+            (BlockKind::DisplayMath, false) => {
+                format!("<span class=\"math math-display\">{}</span>", escape_html(source))
+            }
+            (BlockKind::InlineMath, _) => format!("<span class=\"math math-inline\">{}</span>", escape_html(source)),
+        })
+    }
+
+    /// 将 HTML 中的占位符还原为公式 / 图表元素 (须在 HTML 过滤之后调用)
+    pub fn restore(&self, html: &str) -> String {
+        if self.is_empty() {
+            return html.to_string();
+        }
+        let html = STANDALONE_RE.replace_all(html, |caps: &regex::Captures| {
+            caps[1]
+                .parse()
+                .ok()
+                .and_then(|i| self.render(i, true))
+                .unwrap_or_else(|| caps[0].to_string())
+        });
+        PLACEHOLDER_RE
+            .replace_all(&html, |caps: &regex::Captures| {
+                caps[1]
+                    .parse()
+                    .ok()
+                    .and_then(|i| self.render(i, false))
+                    .unwrap_or_default()
+            })
+            .to_string()
+    }
+}
+
+/// 查找行内公式的结束 `$` (第一个未转义的 `$`)
+/// 它不能紧跟在空白之后，后面也不能是数字，否则不构成公式 (避免 "$5 和 $10" 被识别为公式)
+fn find_inline_math_end(rest: &str) -> Option<usize> {
+    let bytes = rest.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'$' => {
+                let after_space = i == 0 || bytes[i - 1].is_ascii_whitespace();
+                let before_digit = bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit());
+                return (!after_space && !before_digit).then_some(i);
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// 处理单行中的行内代码、`$$...$$` 和 `$...$`
+fn protect_inline(line: &str, blocks: &mut ProtectedBlocks) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(pos) = rest.find(['`', '\\', '$']) {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if rest.starts_with('`') {
+            // 行内代码原样保留：找到相同长度的反引号序列
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let fence = &rest[..ticks];
+            match rest[ticks..].find(fence) {
+                Some(end) => {
+                    let span_end = ticks + end + ticks;
+                    output.push_str(&rest[..span_end]);
+                    rest = &rest[span_end..];
+                }
+                None => {
+                    output.push_str(fence);
+                    rest = &rest[ticks..];
+                }
+            }
+        } else if rest.starts_with('\\') {
+            // 转义字符 (包括 \$) 原样保留
+            let escaped_len = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+            output.push_str(&rest[..escaped_len]);
+            rest = &rest[escaped_len..];
+        } else if let Some(inner) = rest.strip_prefix("$$") {
+            match inner.find("$$") {
+                Some(end) if !inner[..end].trim().is_empty() => {
+                    output.push_str(&blocks.push(BlockKind::DisplayMath, inner[..end].trim()));
+                    rest = &inner[end + 2..];
+                }
+                _ => {
+                    output.push_str("$$");
+                    rest = inner;
+                }
+            }
+        } else {
+            let inner = &rest[1..];
+            let opens = inner.chars().next().is_some_and(|c| !c.is_whitespace());
+            match find_inline_math_end(inner).filter(|_| opens) {
+                Some(end) => {
+                    output.push_str(&blocks.push(BlockKind::InlineMath, &inner[..end]));
+                    rest = &inner[end + 1..];
+                }
+                None => {
+                    output.push('$');
+                    rest = inner;
+                }
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// 去掉文本中的占位符字符：否则用户输入的 "\u{E000}0\u{E001}" (包括写在属性值中的)
+/// 会在 HTML 过滤之后被还原为标签。凡是在还原之前插入 HTML 的内容都要先经过这里
+pub fn strip_placeholder_chars(text: &str) -> Cow<'_, str> {
+    if text.contains([PLACEHOLDER_START, PLACEHOLDER_END]) {
+        Cow::Owned(text.replace([PLACEHOLDER_START, PLACEHOLDER_END], ""))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// 将公式与 Mermaid 块替换为占位符，返回处理后的 Markdown 及占位内容
+/// 围栏代码块 (mermaid 除外) 和行内代码中的内容保持不变
+pub fn protect_special_blocks(content: &str) -> (String, ProtectedBlocks) {
+    let content = strip_placeholder_chars(content);
+    let content = content.as_ref();
+    let mut blocks = ProtectedBlocks::default();
+    let mut output = String::with_capacity(content.len());
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        let indent = &line[..line.len() - line.trim_start().len()];
+
+        // 围栏代码块
+        let fence = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f));
+        if let Some(fence) = fence {
+            let is_mermaid = trimmed[fence.len()..].trim().eq_ignore_ascii_case("mermaid");
+            let mut body = String::new();
+            let mut closed = false;
+            let mut raw = String::from(line);
+            for inner in lines.by_ref() {
+                raw.push_str(inner);
+                if inner.trim().starts_with(fence) {
+                    closed = true;
+                    break;
+                }
+                body.push_str(inner);
+            }
+            if is_mermaid && closed {
+                output.push('\n');
+                output.push_str(indent);
+                output.push_str(&blocks.push(BlockKind::Mermaid, body.trim_end()));
+                output.push_str("\n\n");
+            } else {
+                output.push_str(&raw);
+            }
+            continue;
+        }
+
+        // 多行块级公式: 以 $$ 开头、本行内没有闭合
+        if let Some(first) = trimmed.strip_prefix("$$").filter(|rest| !rest.contains("$$")) {
+            let mut source = String::from(first);
+            let mut raw = String::from(line);
+            let mut closed = false;
+            for inner in lines.by_ref() {
+                raw.push_str(inner);
+                let inner_trimmed = inner.trim();
+                if let Some(last) = inner_trimmed.strip_suffix("$$") {
+                    source.push('\n');
+                    source.push_str(last);
+                    closed = true;
+                    break;
+                }
+                source.push('\n');
+                source.push_str(inner.trim_end_matches(['\r', '\n']));
+            }
+            if closed && !source.trim().is_empty() {
+                output.push('\n');
+                output.push_str(indent);
+                output.push_str(&blocks.push(BlockKind::DisplayMath, source.trim()));
+                output.push_str("\n\n");
+            } else {
+                // 未闭合时按普通文本处理
+                output.push_str(&raw);
+            }
+            continue;
+        }
+
+        output.push_str(&protect_inline(line, &mut blocks));
+    }
+
+    (output, blocks)
+}
@@ -0,0 +1,295 @@
+// src-tauri/src/commands/tasks.rs
+// Markdown 任务 (- [ ] / - [x]) 提取与全库任务列表
+
+use crate::commands::fs::save_file;
+use crate::commands::path_utils::to_absolute_path;
+use crate::commands::properties::split_frontmatter;
+use crate::commands::utils::escape_like;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{command, State};
+
+/// 任务行: 缩进 + 列表标记 + [ ]/[x] + 文本
+static TASK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*(?:[-*+]|\d+[.)])\s+\[)([ xX])(\]\s+)(.*)$").unwrap());
+/// 截止日期: `📅 2026-10-20`、`due: 2026-10-20` 或 `due:: 2026-10-20`
+static DUE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:📅\s*|\bdue::?\s*)(\d{4}-\d{2}-\d{2})").unwrap());
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|\s)#([\w/-]+)").unwrap());
+
+#[derive(Debug, Clone)]
+pub struct ExtractedTask {
+    pub line_number: i64, // 从 1 开始
+    pub text: String,
+    pub completed: bool,
+    pub due_date: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskItem {
+    pub id: i64,
+    pub path: String,
+    pub title: String,
+    pub line_number: i64,
+    pub text: String,
+    pub completed: bool,
+    pub due_date: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// query_tasks 的过滤条件，所有字段均可省略
+#[derive(Debug, Default, Deserialize)]
+pub struct TaskFilter {
+    pub completed: Option<bool>,
+    pub folder: Option<String>,
+    pub path: Option<String>,
+    pub tag: Option<String>,
+    pub due_before: Option<String>, // 含当天
+    pub due_after: Option<String>,  // 含当天
+    pub has_due: Option<bool>,
+    pub text: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// 从笔记内容中提取任务 (跳过 frontmatter 和围栏代码块)
+pub fn extract_tasks(content: &str) -> Vec<ExtractedTask> {
+    let body_start = split_frontmatter(content).map(|(_, start)| start).unwrap_or(0);
+    let skipped_lines = content[..body_start].matches('\n').count();
+
+    let mut tasks = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for (index, line) in content.lines().enumerate().skip(skipped_lines) {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        if let Some(caps) = TASK_RE.captures(line) {
+            let text = caps[4].trim().to_string();
+            let due_date = DUE_RE.captures(&text).map(|c| c[1].to_string());
+            let mut tags: Vec<String> = TAG_RE
+                .captures_iter(&text)
+                .map(|c| c[1].to_lowercase())
+                .collect();
+            tags.sort();
+            tags.dedup();
+            tasks.push(ExtractedTask {
+                line_number: index as i64 + 1,
+                text,
+                completed: !caps[2].trim().is_empty(),
+                due_date,
+                tags,
+            });
+        }
+    }
+    tasks
+}
+
+/// 索引时调用：重建该文件的任务记录
+pub fn update_tasks_for_file(
+    conn: &mut Connection,
+    root_path: &str,
+    relative_path: &str,
+) -> Result<(), String> {
+    let absolute_path = to_absolute_path(Path::new(root_path), Path::new(relative_path));
+    let content = match std::fs::read_to_string(&absolute_path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("  ☑️ [tasks] 读取文件失败: {}: {}", relative_path, e);
+            return Ok(());
+        }
+    };
+
+    let file_id: i64 = match conn
+        .query_row("SELECT id FROM files WHERE path = ?1", params![relative_path], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("查询文件ID失败: {}", e))?
+    {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+
+    let tasks = extract_tasks(&content);
+
+    let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+    tx.execute(
+        "DELETE FROM task_tags WHERE task_id IN (SELECT id FROM tasks WHERE file_id = ?1)",
+        params![file_id],
+    )
+    .map_err(|e| format!("删除旧任务标签失败: {}", e))?;
+    tx.execute("DELETE FROM tasks WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("删除旧任务失败: {}", e))?;
+    for task in &tasks {
+        tx.execute(
+            "INSERT INTO tasks (file_id, line_number, text, completed, due_date) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![file_id, task.line_number, task.text, task.completed, task.due_date],
+        )
+        .map_err(|e| format!("插入任务失败: {}", e))?;
+        let task_id = tx.last_insert_rowid();
+        for tag in &task.tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO task_tags (task_id, tag) VALUES (?1, ?2)",
+                params![task_id, tag],
+            )
+            .map_err(|e| format!("插入任务标签失败: {}", e))?;
+        }
+    }
+    tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
+
+    if !tasks.is_empty() {
+        println!("  ☑️ [tasks] {} 个任务: {}", tasks.len(), relative_path);
+    }
+    Ok(())
+}
+
+#[command]
+pub async fn query_tasks(filter: Option<TaskFilter>, state: State<'_, AppState>) -> Result<Vec<TaskItem>, String> {
+    let filter = filter.unwrap_or_default();
+    let db_pool = state.db_pool.lock().unwrap();
+    let conn = db_pool.as_ref().ok_or("数据库未初始化")?.get().map_err(|e| e.to_string())?;
+
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut values: Vec<SqlValue> = Vec::new();
+
+    if let Some(completed) = filter.completed {
+        conditions.push("t.completed = ?");
+        values.push(SqlValue::Integer(completed as i64));
+    }
+    if let Some(folder) = filter.folder.as_deref().map(|f| f.trim().trim_matches('/')).filter(|f| !f.is_empty()) {
+        conditions.push("f.path LIKE ? || '/%' ESCAPE '\\'");
+        values.push(SqlValue::Text(escape_like(folder)));
+    }
+    if let Some(path) = filter.path {
+        conditions.push("f.path = ?");
+        values.push(SqlValue::Text(path));
+    }
+    if let Some(tag) = filter.tag {
+        conditions.push("t.id IN (SELECT task_id FROM task_tags WHERE tag = ?)");
+        values.push(SqlValue::Text(tag.trim().trim_start_matches('#').to_lowercase()));
+    }
+    if let Some(before) = filter.due_before {
+        conditions.push("t.due_date IS NOT NULL AND t.due_date <= ?");
+        values.push(SqlValue::Text(before));
+    }
+    if let Some(after) = filter.due_after {
+        conditions.push("t.due_date IS NOT NULL AND t.due_date >= ?");
+        values.push(SqlValue::Text(after));
+    }
+    match filter.has_due {
+        Some(true) => conditions.push("t.due_date IS NOT NULL"),
+        Some(false) => conditions.push("t.due_date IS NULL"),
+        None => {}
+    }
+    if let Some(text) = filter.text.filter(|t| !t.trim().is_empty()) {
+        conditions.push("instr(lower(t.text), lower(?)) > 0");
+        values.push(SqlValue::Text(text));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let sql = format!(
+        "SELECT t.id, f.path, f.title, t.line_number, t.text, t.completed, t.due_date,
+                (SELECT group_concat(tag, ',') FROM task_tags WHERE task_id = t.id)
+         FROM tasks t INNER JOIN files f ON f.id = t.file_id
+         {}
+         ORDER BY t.completed ASC, t.due_date IS NULL, t.due_date ASC, f.path, t.line_number
+         LIMIT {}",
+        where_clause,
+        filter.limit.unwrap_or(500).max(0)
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let tasks_iter = stmt
+        .query_map(params_from_iter(values), |row| {
+            let tags: Option<String> = row.get(7)?;
+            Ok(TaskItem {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                title: row.get::<_, Option<String>>(2)?.unwrap_or_else(|| "无标题".to_string()),
+                line_number: row.get(3)?,
+                text: row.get(4)?,
+                completed: row.get(5)?,
+                due_date: row.get(6)?,
+                tags: tags
+                    .map(|t| t.split(',').map(|s| s.to_string()).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut tasks = Vec::new();
+    for task in tasks_iter {
+        tasks.push(task.map_err(|e| e.to_string())?);
+    }
+    Ok(tasks)
+}
+
+/// 切换指定行任务的完成状态，通过 save_file 写回，返回新的完成状态
+/// expected_text 用于防止文件在前端刷新前已被修改导致切换了错误的行
+#[command]
+pub async fn toggle_task(
+    root_path: String,
+    relative_path: String,
+    line_number: i64,
+    expected_text: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let absolute_path = to_absolute_path(Path::new(&root_path), Path::new(&relative_path));
+    let content = std::fs::read_to_string(&absolute_path).map_err(|e| format!("读取文件失败: {}", e))?;
+
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let index = usize::try_from(line_number - 1)
+        .ok()
+        .filter(|i| *i < lines.len())
+        .ok_or_else(|| format!("行号超出范围: {}", line_number))?;
+
+    let raw_line = lines[index];
+    let line_body = raw_line.trim_end_matches(['\r', '\n']);
+    let line_ending = &raw_line[line_body.len()..];
+
+    let caps = TASK_RE
+        .captures(line_body)
+        .ok_or_else(|| format!("第 {} 行不是任务", line_number))?;
+    if let Some(expected) = expected_text {
+        if caps[4].trim() != expected.trim() {
+            return Err("任务内容已变化，请刷新后重试".to_string());
+        }
+    }
+
+    let now_completed = caps[2].trim().is_empty();
+    let new_line = format!(
+        "{}{}{}{}{}",
+        &caps[1],
+        if now_completed { "x" } else { " " },
+        &caps[3],
+        &caps[4],
+        line_ending
+    );
+    lines[index] = &new_line;
+    let new_content: String = lines.concat();
+
+    // 通过 save_file 走统一的保存流程 (锁、链接、历史、索引 —— 索引时会重新提取任务)
+    save_file(root_path, relative_path, new_content, state).await?;
+    Ok(now_completed)
+}
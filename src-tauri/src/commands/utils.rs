@@ -1,43 +1,178 @@
 // src-tauri/src/commands/utils.rs
 
+use crate::commands::embeds::expand_embeds;
+use crate::commands::highlight::{highlight_code_blocks, load_highlight_config};
+use crate::commands::links::find_link_targets;
+use crate::commands::outline::{collect_headings, render_toc_html, SlugGenerator};
+use crate::commands::properties::split_frontmatter;
+use crate::commands::query::{execute_note_query, NoteQueryResult, QueryKind};
+use crate::commands::sanitize::{load_sanitize_config, sanitize_html};
+use crate::commands::special_blocks::{protect_special_blocks, strip_placeholder_chars};
 use crate::AppState;
-use pulldown_cmark::{html, Parser};
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
 use regex::Regex;
-use rusqlite::{params, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension};
 use tauri::{command, State};
 
-#[command]
-pub async fn parse_markdown(
-    content: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let db_pool_lock = state.db_pool.lock().unwrap();
-    let db_pool = db_pool_lock.as_ref().ok_or("数据库未初始化")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+/// 转义 HTML 特殊字符
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
 
-    let re = Regex::new(r"\[\[([^\]]+)\]\]").unwrap();
-    let processed_content = re.replace_all(&content, |caps: &regex::Captures| {
-        let link_target = caps[1].trim();
-        let mut final_path: Option<String> = None;
-
-        if let Ok(Some(path)) = conn.query_row(
-            "SELECT path FROM files WHERE title = ?1 LIMIT 1",
-            params![link_target],
-            |row| row.get(0),
-        ).optional() {
-            final_path = path;
+/// 转义 SQL LIKE 模式中的通配符 (% 与 _)，配合 `ESCAPE '\'` 使用
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Markdown 解析选项：启用 pulldown-cmark 的全部扩展 (GFM 表格、任务列表、删除线、脚注、标题属性、智能标点)
+/// 渲染与大纲提取共用，保证标题锚点一致
+pub fn markdown_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_SMART_PUNCTUATION
+        | Options::ENABLE_HEADING_ATTRIBUTES
+}
+
+fn query_value_to_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items.iter().map(query_value_to_text).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn render_query_result(result: &NoteQueryResult) -> String {
+    let note_link = |path: &str, title: &str| {
+        format!(
+            "<a href=\"#\" class=\"internal-link\" data-path=\"{}\">{}</a>",
+            escape_html(path),
+            escape_html(title)
+        )
+    };
+
+    if result.rows.is_empty() {
+        return "<div class=\"note-query note-query-empty\">没有匹配的笔记</div>".to_string();
+    }
+
+    match result.kind {
+        QueryKind::List => {
+            let items: String = result.rows.iter().map(|row| {
+                let extra = row.values.first()
+                    .map(query_value_to_text)
+                    .filter(|v| !v.is_empty())
+                    .map(|v| format!(" <span class=\"note-query-value\">{}</span>", escape_html(&v)))
+                    .unwrap_or_default();
+                format!("<li>{}{}</li>", note_link(&row.path, &row.title), extra)
+            }).collect();
+            format!("<ul class=\"note-query note-query-list\">{}</ul>", items)
         }
+        QueryKind::Table => {
+            let header: String = std::iter::once("笔记".to_string())
+                .chain(result.columns.iter().cloned())
+                .map(|c| format!("<th>{}</th>", escape_html(&c)))
+                .collect();
+            let body: String = result.rows.iter().map(|row| {
+                let cells: String = row.values.iter()
+                    .map(|v| format!("<td>{}</td>", escape_html(&query_value_to_text(v))))
+                    .collect();
+                format!("<tr><td>{}</td>{}</tr>", note_link(&row.path, &row.title), cells)
+            }).collect();
+            format!(
+                "<table class=\"note-query note-query-table\"><thead><tr>{}</tr></thead><tbody>{}</tbody></table>",
+                header, body
+            )
+        }
+    }
+}
 
-        if final_path.is_none() {
-            let path_pattern = format!("%{}.md", link_target);
-            if let Ok(Some(path)) = conn.query_row(
-                "SELECT path FROM files WHERE path LIKE ?1 LIMIT 1",
-                params![path_pattern],
-                |row| row.get(0),
-            ).optional() {
-                final_path = path;
+/// [TOC] 标记在渲染过程中的占位符 (HTML 注释会作为原样 HTML 块输出)
+const TOC_PLACEHOLDER: &str = "<!--cheetah-toc-->";
+
+/// 将独占一行的 [TOC] 标记 (代码块之外) 替换为占位符，返回是否存在标记
+fn replace_toc_markers(content: &str) -> (String, bool) {
+    let mut output = String::with_capacity(content.len());
+    let mut fence: Option<&str> = None;
+    let mut found = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
             }
+        } else if trimmed.starts_with("```") {
+            fence = Some("```");
+        } else if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+        } else if trimmed.eq_ignore_ascii_case("[TOC]") {
+            output.push('\n');
+            output.push_str(TOC_PLACEHOLDER);
+            output.push_str("\n\n");
+            found = true;
+            continue;
         }
+        output.push_str(line);
+    }
+    (output, found)
+}
+
+/// 将 ```query 代码块替换为实时查询结果 (单行 HTML 块，前后留空行以免被 Markdown 合并)
+fn render_query_blocks(conn: &Connection, content: &str) -> String {
+    let re = Regex::new(r"(?ms)^```query[ \t]*\r?\n(.*?)^```[ \t]*\r?$").unwrap();
+    re.replace_all(content, |caps: &regex::Captures| {
+        let html = match execute_note_query(conn, caps[1].trim()) {
+            Ok(result) => render_query_result(&result),
+            Err(e) => format!("<div class=\"note-query note-query-error\">{}</div>", escape_html(&e)),
+        };
+        // 查询结果中的标题、属性值来自其他笔记，同样去掉占位符字符
+        format!("\n{}\n", strip_placeholder_chars(&html))
+    })
+    .to_string()
+}
+
+/// 按 [[链接]] 的目标查找笔记路径 (规则见 links::find_link_targets)
+/// 有歧义时取第一个匹配，链接检查报告中会单独列出
+pub fn resolve_wikilink(conn: &Connection, link_target: &str) -> Option<String> {
+    find_link_targets(conn, link_target)
+        .ok()?
+        .into_iter()
+        .next()
+        .map(|(_, path)| path)
+}
+
+/// 渲染上下文：嵌入笔记时需要读取文件并检测循环
+pub struct RenderContext<'a> {
+    pub conn: &'a Connection,
+    pub root_path: Option<String>,
+    /// 当前嵌入链上的笔记路径 (含正在渲染的笔记)
+    pub embed_stack: Vec<String>,
+}
+
+/// 将 Markdown 渲染为过滤后的 HTML；source_path 为内容所属笔记 (用于解析相对附件)
+pub fn render_markdown_html(ctx: &mut RenderContext, content: &str, source_path: Option<&str>) -> String {
+    // frontmatter 属于元数据，不参与渲染 (属性面板单独展示)
+    let body_start = split_frontmatter(content).map(|(_, start)| start).unwrap_or(0);
+    let content = &content[body_start..];
+
+    // 公式与 Mermaid 块先替换为占位符，避免被 Markdown 语法和 HTML 过滤破坏
+    let (content, mut special_blocks) = protect_special_blocks(content);
+    // ![[嵌入]] 渲染为独立的 HTML 片段，同样以占位符插入
+    let content = expand_embeds(ctx, &content, source_path, &mut special_blocks);
+    // 再展开 ```query 代码块，避免查询语句中的 [[链接]] 被当作 Wikilink 处理
+    let content = render_query_blocks(ctx.conn, &content);
+    let (content, has_toc) = replace_toc_markers(&content);
+
+    let re = Regex::new(r"\[\[([^\]]+)\]\]").unwrap();
+    let processed_content = re.replace_all(&content, |caps: &regex::Captures| {
+        let link_target = caps[1].trim();
+        let final_path = resolve_wikilink(ctx.conn, link_target);
 
         // ▼▼▼ 【核心修改】将 r#""# 替换为带 \" 转义的普通字符串 ▼▼▼
         match final_path {
@@ -52,11 +187,58 @@ pub async fn parse_markdown(
         }
     });
 
-    let parser = Parser::new(&processed_content);
+    // 为标题生成锚点 id (与 get_note_outline 的 id 规则一致)，[TOC] 依赖这些锚点
+    let events: Vec<Event> = Parser::new_ext(&processed_content, markdown_options()).collect();
+    let mut slug_generator = SlugGenerator::default();
+    let toc_entries: Vec<(u8, String, String)> = collect_headings(&events)
+        .into_iter()
+        .map(|(level, text, explicit_id)| {
+            let id = slug_generator.heading_id(&text, explicit_id.as_deref());
+            (level, text, id)
+        })
+        .collect();
+
+    let mut heading_index = 0;
+    let events = events.into_iter().map(|event| match event {
+        Event::Start(Tag::Heading(level, _, classes)) => {
+            let id = toc_entries[heading_index].2.as_str();
+            heading_index += 1;
+            Event::Start(Tag::Heading(level, Some(id), classes))
+        }
+        other => other,
+    });
+    let events = highlight_code_blocks(events, &load_highlight_config(ctx.conn));
     let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
+    html::push_html(&mut html_output, events.into_iter());
+
+    // 占位符是 HTML 注释，会被过滤器移除，因此在过滤之前替换
+    if has_toc {
+        html_output = html_output.replace(TOC_PLACEHOLDER, &render_toc_html(&toc_entries));
+    }
+
+    // 笔记中的原始 HTML 会原样进入 webview，输出前按白名单过滤
+    html_output = sanitize_html(&html_output, &load_sanitize_config(ctx.conn));
+    special_blocks.restore(&html_output)
+}
+
+/// 渲染笔记；relative_path 为当前笔记路径 (可选)，用于嵌入的循环检测和附件定位
+#[command]
+pub async fn parse_markdown(
+    content: String,
+    relative_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let root_path = state.current_path.lock().unwrap().clone();
+    let db_pool_lock = state.db_pool.lock().unwrap();
+    let db_pool = db_pool_lock.as_ref().ok_or("数据库未初始化")?;
+    let conn = db_pool.get().map_err(|e| e.to_string())?;
 
-    Ok(html_output)
+    let mut ctx = RenderContext {
+        conn: &conn,
+        root_path,
+        embed_stack: relative_path.iter().cloned().collect(),
+    };
+    Ok(render_markdown_html(&mut ctx, &content, relative_path.as_deref()))
 }
 /// 检查索引是否正在更新
 #[command]
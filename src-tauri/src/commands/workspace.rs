@@ -1,10 +1,11 @@
 // src-tauri/src/commands/workspace.rs
-use crate::database::init_database;
+use crate::database::{init_database, DbPool};
+use crate::commands::search::load_analyzer_config;
 use crate::search_core;
 use crate::indexing_jobs; // [新增]
 use crate::AppState;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::{command,State, AppHandle};  // ✅ 添加 AppHandle
 use serde::Serialize;
 
@@ -17,6 +18,20 @@ pub struct WorkspaceInfo {
     pub is_initialized: bool,
 }
 
+/// 工作区搜索索引的目录 (传给 search_core::initialize_index)
+pub fn search_index_dir(workspace_path: &Path) -> PathBuf {
+    workspace_path.join(WORKSPACE_META_DIR).join(".cheetah_index")
+}
+
+/// 索引被重建 (如索引结构升级、分词配置变化) 后，标记所有笔记为未索引，由同步流程重新建立索引
+pub fn mark_all_unindexed(db_pool: &DbPool) -> Result<(), String> {
+    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let count = conn
+        .execute("UPDATE files SET indexed = 0 WHERE is_dir = 0", [])
+        .map_err(|e| e.to_string())?;
+    println!("🔀 索引已重建，{} 个笔记等待重新索引", count);
+    Ok(())
+}
 
 #[command]
 pub async fn check_workspace(workspace_path: String) -> Result<WorkspaceInfo, String> {
@@ -70,9 +85,12 @@ pub async fn initialize_workspace(
     indexing_jobs::set_db_pool(db_pool.clone());
 
     println!("🔍 初始化搜索索引...");
-    let index_dir = meta_dir.join(".cheetah_index");
-    let index = search_core::initialize_index(&index_dir)
+    let analyzer_config = load_analyzer_config(&*db_pool.get().map_err(|e| e.to_string())?);
+    let (index, index_rebuilt) = search_core::initialize_index(&search_index_dir(path), &analyzer_config)
         .map_err(|e| format!("初始化搜索索引失败: {}", e))?;
+    if index_rebuilt {
+        mark_all_unindexed(&db_pool)?;
+    }
     
     // [新增] 启动后台索引Worker
     println!("🔄 启动后台索引Worker...");
@@ -122,9 +140,12 @@ pub async fn load_workspace(
 
     
     println!("🔍 加载搜索索引...");
-    let index_dir = meta_dir.join(".cheetah_index");
-    let index = search_core::initialize_index(&index_dir)
+    let analyzer_config = load_analyzer_config(&*db_pool.get().map_err(|e| e.to_string())?);
+    let (index, index_rebuilt) = search_core::initialize_index(&search_index_dir(path), &analyzer_config)
         .map_err(|e| format!("加载搜索索引失败: {}", e))?;
+    if index_rebuilt {
+        mark_all_unindexed(&db_pool)?;
+    }
     
     // [新增] 启动后台索引Worker
     println!("🔄 启动后台索引Worker...");
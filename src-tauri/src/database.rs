@@ -4,7 +4,8 @@ use r2d2_sqlite::SqliteConnectionManager;
 use std::path::Path;
 use std::fs;
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
 
 pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
 
@@ -126,6 +127,44 @@ fn run_migrations(conn: &Connection) -> Result<(), rusqlite::Error> {
         println!("✅ 'word_count' 字段添加完成！");
     }
 
+    // === 迁移 8: 为 links 表添加 link_type 字段 (wikilink / embed)，并将其加入主键 ===
+    // SQLite 无法修改主键，需要重建表；新数据库的 links 表在步骤 3 中直接以新结构创建
+    let links_exists: bool = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'links'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if links_exists {
+        let mut stmt = conn.prepare("PRAGMA table_info(links)")?;
+        let has_link_type = stmt.query_map([], |row| {
+            let column_name: String = row.get(1)?;
+            Ok(column_name)
+        })?.any(|col| col.as_deref() == Ok("link_type"));
+
+        if !has_link_type {
+            println!("🔀 迁移数据库：正在为 'links' 表添加 'link_type' 字段...");
+            conn.execute_batch(
+                "BEGIN;
+                 CREATE TABLE links_new (
+                     source_file_id  INTEGER,
+                     target_file_id  INTEGER,
+                     link_type       TEXT NOT NULL DEFAULT 'wikilink',
+                     FOREIGN KEY (source_file_id) REFERENCES files (id) ON DELETE CASCADE,
+                     FOREIGN KEY (target_file_id) REFERENCES files (id) ON DELETE CASCADE,
+                     PRIMARY KEY (source_file_id, target_file_id, link_type)
+                 );
+                 INSERT INTO links_new (source_file_id, target_file_id, link_type)
+                     SELECT source_file_id, target_file_id, 'wikilink' FROM links
+                     WHERE source_file_id IN (SELECT id FROM files)
+                       AND target_file_id IN (SELECT id FROM files);
+                 DROP TABLE links;
+                 ALTER TABLE links_new RENAME TO links;
+                 COMMIT;",
+            )?;
+            println!("✅ 'link_type' 字段添加完成！");
+        }
+    }
+
     Ok(())
 }
 
@@ -139,7 +178,9 @@ pub fn init_database(app_data_dir: &Path) -> Result<DbPool> {
     
     println!("🗃️ 数据库路径: {}", db_path.display());
 
-    let manager = SqliteConnectionManager::file(db_path);
+    // 显式开启外键约束，使各表的 ON DELETE CASCADE 生效 (SQLite 默认关闭，取决于编译选项)
+    let manager = SqliteConnectionManager::file(db_path)
+        .with_init(|conn| conn.execute_batch("PRAGMA foreign_keys = ON;"));
     let pool = r2d2::Pool::new(manager)
         .with_context(|| "创建数据库连接池失败")?;
 
@@ -195,14 +236,44 @@ pub fn init_database(app_data_dir: &Path) -> Result<DbPool> {
 		CREATE INDEX IF NOT EXISTS idx_history_datetime ON history (event_datetime);
         CREATE INDEX IF NOT EXISTS idx_history_file_id ON history (file_id);
 		
-		/* links 表 */
+		/* links 表 (link_type: wikilink = [[链接]], embed = ![[嵌入]]) */
         CREATE TABLE IF NOT EXISTS links (
             source_file_id  INTEGER,
             target_file_id  INTEGER,
+            link_type       TEXT NOT NULL DEFAULT 'wikilink',
             FOREIGN KEY (source_file_id) REFERENCES files (id) ON DELETE CASCADE,
             FOREIGN KEY (target_file_id) REFERENCES files (id) ON DELETE CASCADE,
-            PRIMARY KEY (source_file_id, target_file_id)
+            PRIMARY KEY (source_file_id, target_file_id, link_type)
         );
+        CREATE INDEX IF NOT EXISTS idx_links_target ON links (target_file_id);
+
+        /* 未解析的链接 (reason: missing = 目标不存在, ambiguous = 匹配到多个笔记) */
+        CREATE TABLE IF NOT EXISTS unresolved_links (
+            source_file_id  INTEGER NOT NULL,
+            target          TEXT NOT NULL,
+            link_type       TEXT NOT NULL,
+            reason          TEXT NOT NULL,
+            FOREIGN KEY (source_file_id) REFERENCES files (id) ON DELETE CASCADE,
+            PRIMARY KEY (source_file_id, target, link_type)
+        );
+
+        /* 删除笔记时，先将指向它的链接转为断开的链接，以 [[标题]] (Markdown 链接为路径) 作为目标，
+           再删除它的出入链接与未解析链接。显式删除而不依赖 ON DELETE CASCADE：files.id 可能被新文件复用，
+           残留的链接会被新文件继承。删除操作分散在多处，因此用触发器统一处理 */
+        DROP TRIGGER IF EXISTS trg_files_delete_unresolve_links;
+        CREATE TRIGGER trg_files_delete_unresolve_links
+        BEFORE DELETE ON files
+        BEGIN
+            INSERT OR IGNORE INTO unresolved_links (source_file_id, target, link_type, reason)
+                SELECT source_file_id,
+                       CASE WHEN link_type = 'markdown' THEN OLD.path ELSE COALESCE(OLD.title, OLD.path) END,
+                       link_type,
+                       'missing'
+                FROM links
+                WHERE target_file_id = OLD.id AND source_file_id != OLD.id;
+            DELETE FROM links WHERE source_file_id = OLD.id OR target_file_id = OLD.id;
+            DELETE FROM unresolved_links WHERE source_file_id = OLD.id;
+        END;
 		
         /* [新增] 索引任务队列表 */
 		CREATE TABLE IF NOT EXISTS indexing_jobs (
@@ -218,6 +289,108 @@ pub fn init_database(app_data_dir: &Path) -> Result<DbPool> {
 		
 		CREATE INDEX IF NOT EXISTS idx_indexing_jobs_status 
 			ON indexing_jobs (status, created_at);
+
+		/* 笔记属性表 (由 frontmatter 解析而来) */
+		CREATE TABLE IF NOT EXISTS note_properties (
+			file_id     INTEGER NOT NULL,
+			key         TEXT NOT NULL,
+			value_type  TEXT NOT NULL,
+			value_text  TEXT,
+			value_num   REAL,
+			value_json  TEXT NOT NULL,
+			FOREIGN KEY (file_id) REFERENCES files (id) ON DELETE CASCADE,
+			PRIMARY KEY (file_id, key)
+		);
+		CREATE INDEX IF NOT EXISTS idx_note_properties_key ON note_properties (key, value_text);
+
+		/* 删除笔记时显式删除其属性，不依赖 ON DELETE CASCADE，避免复用 id 的新文件继承旧属性 */
+		CREATE TRIGGER IF NOT EXISTS trg_files_delete_properties
+		BEFORE DELETE ON files
+		BEGIN
+			DELETE FROM note_properties WHERE file_id = OLD.id;
+		END;
+
+		/* 笔记别名 (frontmatter 中的 aliases)，用于链接解析、搜索和未链接提及 */
+		CREATE TABLE IF NOT EXISTS aliases (
+			file_id  INTEGER NOT NULL,
+			alias    TEXT NOT NULL COLLATE NOCASE,
+			FOREIGN KEY (file_id) REFERENCES files (id) ON DELETE CASCADE,
+			PRIMARY KEY (file_id, alias)
+		);
+		CREATE INDEX IF NOT EXISTS idx_aliases_alias ON aliases (alias);
+
+		/* 删除笔记时显式删除其别名，否则复用 id 的新文件会被旧别名的 [[链接]] 解析到 */
+		CREATE TRIGGER IF NOT EXISTS trg_files_delete_aliases
+		BEFORE DELETE ON files
+		BEGIN
+			DELETE FROM aliases WHERE file_id = OLD.id;
+		END;
+
+		/* 任务表 (由 Markdown 复选框提取而来) */
+		CREATE TABLE IF NOT EXISTS tasks (
+			id           INTEGER PRIMARY KEY,
+			file_id      INTEGER NOT NULL,
+			line_number  INTEGER NOT NULL,
+			text         TEXT NOT NULL,
+			completed    INTEGER NOT NULL DEFAULT 0,
+			due_date     TEXT,
+			FOREIGN KEY (file_id) REFERENCES files (id) ON DELETE CASCADE
+		);
+		CREATE INDEX IF NOT EXISTS idx_tasks_file_id ON tasks (file_id);
+		CREATE INDEX IF NOT EXISTS idx_tasks_due ON tasks (completed, due_date);
+
+		CREATE TABLE IF NOT EXISTS task_tags (
+			task_id  INTEGER NOT NULL,
+			tag      TEXT NOT NULL,
+			FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE,
+			PRIMARY KEY (task_id, tag)
+		);
+		CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags (tag);
+
+		/* 删除笔记时显式删除其任务，不依赖 ON DELETE CASCADE，避免复用 id 的新文件继承旧任务 */
+		CREATE TRIGGER IF NOT EXISTS trg_files_delete_tasks
+		BEFORE DELETE ON files
+		BEGIN
+			DELETE FROM task_tags WHERE task_id IN (SELECT id FROM tasks WHERE file_id = OLD.id);
+			DELETE FROM tasks WHERE file_id = OLD.id;
+		END;
+
+		/* 全局查找替换的批次，保存替换前后的内容用于撤销 */
+		CREATE TABLE IF NOT EXISTS replace_batches (
+			id              INTEGER PRIMARY KEY,
+			pattern         TEXT NOT NULL,
+			replacement     TEXT NOT NULL,
+			is_regex        INTEGER NOT NULL DEFAULT 0,
+			case_sensitive  INTEGER NOT NULL DEFAULT 1,
+			created_at      DATETIME DEFAULT CURRENT_TIMESTAMP,
+			undone_at       DATETIME
+		);
+
+		CREATE TABLE IF NOT EXISTS replace_batch_files (
+			batch_id     INTEGER NOT NULL,
+			path         TEXT NOT NULL,
+			old_content  TEXT NOT NULL,
+			new_content  TEXT NOT NULL,
+			FOREIGN KEY (batch_id) REFERENCES replace_batches (id) ON DELETE CASCADE,
+			PRIMARY KEY (batch_id, path)
+		);
+
+		/* 保存的搜索 (侧边栏中的智能文件夹)，filters 为 JSON (文件夹、标签、修改时间) */
+		CREATE TABLE IF NOT EXISTS saved_searches (
+			id          INTEGER PRIMARY KEY,
+			name        TEXT NOT NULL UNIQUE,
+			query       TEXT NOT NULL DEFAULT '',
+			filters     TEXT NOT NULL DEFAULT '{}',
+			created_at  DATETIME DEFAULT CURRENT_TIMESTAMP,
+			updated_at  DATETIME DEFAULT CURRENT_TIMESTAMP
+		);
+
+		/* 工作区设置表 (key -> JSON 值) */
+		CREATE TABLE IF NOT EXISTS settings (
+			key         TEXT PRIMARY KEY,
+			value       TEXT NOT NULL,
+			updated_at  DATETIME DEFAULT CURRENT_TIMESTAMP
+		);
 				"
 			).with_context(|| "创建索引和其他表结构失败")?;
 		
@@ -225,4 +398,25 @@ pub fn init_database(app_data_dir: &Path) -> Result<DbPool> {
     println!("✅ 数据库表结构初始化/验证完成");
     
     Ok(pool)
-}
\ No newline at end of file
+}
+
+/// 读取工作区设置 (以 JSON 存储)，不存在或解析失败时返回 None
+pub fn get_setting<T: DeserializeOwned>(conn: &Connection, key: &str) -> Option<T> {
+    let raw: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+        .unwrap_or(None);
+    raw.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// 写入工作区设置 (以 JSON 存储)
+pub fn set_setting<T: Serialize>(conn: &Connection, key: &str, value: &T) -> Result<()> {
+    let json = serde_json::to_string(value).context("序列化设置失败")?;
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, CURRENT_TIMESTAMP)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP",
+        params![key, json],
+    )
+    .with_context(|| format!("保存设置失败: {}", key))?;
+    Ok(())
+}
@@ -8,7 +8,7 @@ use std::sync::Arc;
 use tantivy::Index;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension};
 use anyhow::Result;
 use crate::database::DbPool;
 use std::sync::Mutex;
@@ -303,13 +303,14 @@ fn process_job(
             let mtime = get_file_mtime(&absolute_path);
             
             // 步骤 3: 更新 files 表
-            let conn = db_pool.get()?;
+            let mut conn = db_pool.get()?;
             conn.execute(
                 "UPDATE files 
                  SET indexed = 1, last_modified = ?1, size = ?2, word_count = ?3 
                  WHERE path = ?4",
                 params![mtime, file_size, file_word_count, relative_path],
             )?;
+            index_note_metadata(&mut conn, root_path, relative_path);
             
             // 步骤 4: ★★★ 释放 L1/L2 锁 ★★★
             SAVE_TRACKER.app_activity_locks.lock().unwrap().remove(relative_path);
@@ -342,13 +343,14 @@ fn process_job(
             let mtime = get_file_mtime(&absolute_path);
 
             // 步骤 3: 更新数据库 (fs.rs 中可能已经更新了 path，这里确保其他元数据被更新)
-            let conn = db_pool.get()?;
+            let mut conn = db_pool.get()?;
             conn.execute(
                 "UPDATE files 
                  SET indexed = 1, last_modified = ?1, size = ?2, word_count = ?3 
                  WHERE path = ?4",
                 params![mtime, file_size, file_word_count, new_relative_path],
             )?;
+            index_note_metadata(&mut conn, root_path, new_relative_path);
             
             // 步骤 4: ★★★ 释放 L1/L2 锁 (新旧路径都释放) ★★★
             {
@@ -376,6 +378,17 @@ fn process_job(
     Ok(())
 }
 
+/// 索引时提取笔记元数据 (frontmatter 属性、标题、任务等)
+/// 元数据提取失败只记录日志，不影响索引任务本身的成败
+fn index_note_metadata(conn: &mut Connection, root_path: &str, relative_path: &str) {
+    if let Err(e) = crate::commands::properties::update_properties_for_file(conn, root_path, relative_path) {
+        eprintln!("⚠️ [索引] 更新笔记属性失败 ({}): {}", relative_path, e);
+    }
+    if let Err(e) = crate::commands::tasks::update_tasks_for_file(conn, root_path, relative_path) {
+        eprintln!("⚠️ [索引] 更新任务失败 ({}): {}", relative_path, e);
+    }
+}
+
 // ============================================================================
 // 7. 数据库队列辅助函数 (不变)
 // ============================================================================
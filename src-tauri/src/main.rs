@@ -8,6 +8,7 @@ use std::sync::{Arc, Mutex};
 
 mod file_watcher;
 mod search_core;
+mod search_query;
 mod commands;
 mod database;
 mod indexing_jobs; // [新增] 导入索引任务模块
@@ -51,11 +52,24 @@ pub fn run() {
             commands::fs::delete_folder,
             commands::fs::rename_item,
             commands::fs::move_item,
+            commands::replace::preview_replace,
+            commands::replace::apply_replace,
+            commands::replace::undo_replace,
+            commands::replace::list_replace_batches,
 			
             // 搜索命令
             commands::search::initialize_index_command,
             commands::search::index_files,
             commands::search::search_notes,
+            commands::search::check_search_query,
+            commands::literal_search::search_literal,
+            commands::search::search_note_titles,
+            commands::quick_open::quick_open,
+            commands::search::get_search_analyzer_config,
+            commands::search::set_search_analyzer_config,
+            commands::search::get_search_ranking_config,
+            commands::search::set_search_ranking_config,
+            commands::search::explain_search_result,
             commands::search::ensure_index_is_loaded,
             commands::search::release_index,
             
@@ -79,13 +93,53 @@ pub fn run() {
             commands::links::debug_get_all_links,
             commands::links::get_backlinks,
             commands::links::get_graph_data,
+            commands::links::get_link_health,
+            commands::graph::get_local_graph,
+            commands::graph::find_path,
+            commands::graph::get_graph_metrics,
+            commands::related::get_related_notes,
+            commands::mentions::get_unlinked_mentions,
+            commands::mentions::convert_mention_to_link,
             commands::path_utils::migrate_paths_to_relative,
             commands::history::get_history,
 			commands::pins::favorite_note,      // ✅ 新增
 			commands::pins::unfavorite_note,    // ✅ 新增
 			commands::pins::get_favorited_notes,// ✅ 新增
 			commands::pins::is_favorited,      // ✅ 新增
-			commands::pins::is_pinned
+			commands::pins::is_pinned,
+
+            // 保存的搜索 (智能文件夹)
+            commands::saved_searches::create_saved_search,
+            commands::saved_searches::list_saved_searches,
+            commands::saved_searches::update_saved_search,
+            commands::saved_searches::delete_saved_search,
+            commands::saved_searches::execute_saved_search,
+
+            // 周期笔记命令
+            commands::periodic::open_periodic_note,
+            commands::periodic::list_periodic_notes,
+            commands::periodic::get_periodic_config,
+            commands::periodic::set_periodic_config,
+
+            // 笔记属性 (frontmatter) 命令
+            commands::properties::get_note_properties,
+            commands::properties::set_note_property,
+            commands::properties::remove_note_property,
+            commands::query::run_note_query,
+
+            // 任务命令
+            commands::tasks::query_tasks,
+            commands::tasks::toggle_task,
+            // 大纲命令
+            commands::outline::get_note_outline,
+            // 渲染安全过滤
+            commands::sanitize::get_sanitize_config,
+            commands::sanitize::set_sanitize_config,
+            // 代码高亮
+            commands::highlight::get_highlight_config,
+            commands::highlight::set_highlight_config,
+            commands::highlight::list_highlight_themes,
+            commands::highlight::get_highlight_css
         ])
         .setup(|app| {
             println!("🚀 CheetahNote 正在启动...");
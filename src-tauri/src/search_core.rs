@@ -3,83 +3,657 @@ use anyhow::{Context, Result};
 use once_cell::sync::Lazy;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::params;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tantivy::collector::TopDocs;
 use tantivy::directory::MmapDirectory;
-use tantivy::query::QueryParser;
+use tantivy::query::{
+    BooleanQuery, BoostQuery, MoreLikeThisQuery, Occur, PhraseQuery, Query, QueryParser, RegexQuery, TermQuery,
+};
 use tantivy::schema::{
-    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, INDEXED, STORED,
+    Field, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED, STORED,
 };
 use tantivy::snippet::SnippetGenerator;
-use tantivy::tokenizer::{LowerCaser, RemoveLongFilter, TextAnalyzer};
-use tantivy::{doc, Index, IndexWriter, ReloadPolicy, TantivyDocument};
+use tantivy::tokenizer::{Language, LowerCaser, RemoveLongFilter, Stemmer, StopWordFilter, TextAnalyzer};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use tantivy::{
+    doc, DocAddress, DocId, Index, IndexWriter, ReloadPolicy, Score, Searcher, SegmentReader, TantivyDocument, TantivyError, Term,
+};
  
 // 改为
-use crate::commands::path_utils::to_absolute_path;  // ✅ 只保留使用的
+use crate::commands::path_utils::to_absolute_path;
+use crate::search_query::{parse_search_query, QueryContext};  // ✅ 只保留使用的
+use crate::commands::properties::{frontmatter_aliases, note_title, parse_frontmatter};
+use pinyin::ToPinyin;
 
 
 
 static JIEBA_TOKENIZER: Lazy<tantivy_jieba::JiebaTokenizer> =
     Lazy::new(|| tantivy_jieba::JiebaTokenizer {});
 
+/// 正文分词器：jieba 切分 + 小写 + 停用词 + 词干提取
+const CONTENT_TOKENIZER: &str = "jieba";
+/// 标题分词器：不去除停用词，短标题中的每个词都可搜索
+const TITLE_TOKENIZER: &str = "jieba_title";
+
+/// 分词配置版本，分词流程变化时递增，使旧索引自动重建
+const ANALYZER_VERSION: u32 = 1;
+/// 索引目录中记录建立索引时所用分词配置的文件
+const ANALYZER_CONFIG_FILE: &str = "analyzer.json";
+
+/// 可选的词干提取语言 (设置中的名称, tantivy 语言)
+pub const SEARCH_LANGUAGES: &[(&str, Language)] = &[
+    ("english", Language::English),
+    ("french", Language::French),
+    ("german", Language::German),
+    ("spanish", Language::Spanish),
+    ("portuguese", Language::Portuguese),
+    ("italian", Language::Italian),
+    ("dutch", Language::Dutch),
+    ("russian", Language::Russian),
+    ("swedish", Language::Swedish),
+    ("norwegian", Language::Norwegian),
+    ("danish", Language::Danish),
+    ("finnish", Language::Finnish),
+];
+
+/// 搜索分词设置
+/// language 为拉丁字母词语的词干提取与停用词语言，"none" 表示只转小写
+/// jieba 会把中文和英文切分为不同的词，因此词干提取与停用词只作用于英文等拉丁字母词语
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyzerConfig {
+    pub language: String,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        AnalyzerConfig {
+            language: "english".to_string(),
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    fn stemmer_language(&self) -> Option<Language> {
+        SEARCH_LANGUAGES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.language))
+            .map(|(_, language)| *language)
+    }
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.language.eq_ignore_ascii_case("none") || self.stemmer_language().is_some() {
+            Ok(())
+        } else {
+            Err(format!("不支持的搜索语言: {}", self.language))
+        }
+    }
+}
+
+/// 记录在索引目录中的分词配置
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct StoredAnalyzerConfig {
+    version: u32,
+    language: String,
+}
+
+impl StoredAnalyzerConfig {
+    fn current(config: &AnalyzerConfig) -> Self {
+        StoredAnalyzerConfig {
+            version: ANALYZER_VERSION,
+            language: config.language.to_lowercase(),
+        }
+    }
+}
+
+fn build_analyzer(language: Option<Language>, stop_words: bool) -> TextAnalyzer {
+    let mut builder = TextAnalyzer::builder(JIEBA_TOKENIZER.clone())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .dynamic();
+    if let Some(language) = language {
+        if stop_words {
+            if let Some(filter) = StopWordFilter::new(language) {
+                builder = builder.filter_dynamic(filter);
+            }
+        }
+        builder = builder.filter_dynamic(Stemmer::new(language));
+    }
+    builder.build()
+}
+
+/// 按配置注册正文与标题的分词器
+pub fn register_analyzers(index: &Index, config: &AnalyzerConfig) {
+    let language = config.stemmer_language();
+    index.tokenizers().register(CONTENT_TOKENIZER, build_analyzer(language, true));
+    index.tokenizers().register(TITLE_TOKENIZER, build_analyzer(language, false));
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub path: String,
     pub title: String,
+    pub title_html: String,                // 标题中的命中用 <mark> 标出
+    pub title_ranges: Vec<(usize, usize)>, // 标题中命中的范围 [start, end)，UTF-16 偏移
     pub snippet: String,
+    pub snippets: Vec<SearchSnippet>,      // 正文中命中最多的几行，按行号排列
+    pub score: f32,
+    pub fuzzy: bool, // 精确搜索无结果时的模糊 (容错) 匹配结果
+}
+
+/// 搜索结果中的一段摘要：正文中命中查询的一行 (过长时截取命中附近的一段)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSnippet {
+    pub line: usize,                 // 从 1 开始
+    pub column: usize,               // 该行第一个命中的列，从 1 开始，UTF-16 单位，与编辑器一致
+    pub text: String,                // 纯文本
+    pub html: String,                // 命中处用 <mark> 包裹
+    pub ranges: Vec<(usize, usize)>, // 命中在 text 中的范围 [start, end)，UTF-16 偏移
 }
 pub struct SchemaFields {
     pub id: Field,
     pub path: Field,
     pub title: Field,
     pub content: Field,
+    pub title_pinyin: Field,
+    pub modified: Field,
 }
 
 pub fn build_schema() -> (Schema, SchemaFields) {
     let mut schema_builder = Schema::builder();
-    let id = schema_builder.add_u64_field("id", INDEXED | STORED);
+    // id 与 modified 为快速字段，排序时按文档读取 (见 ranked_top_docs)
+    let id = schema_builder.add_u64_field("id", INDEXED | STORED | FAST);
     let path = schema_builder.add_text_field("path", tantivy::schema::STRING | STORED);
-    let text_field_indexing = TextFieldIndexing::default()
-        .set_tokenizer("jieba")
+    let title_indexing = TextFieldIndexing::default()
+        .set_tokenizer(TITLE_TOKENIZER)
         .set_index_option(IndexRecordOption::WithFreqsAndPositions);
     let title_options = TextOptions::default()
-        .set_indexing_options(text_field_indexing.clone())
+        .set_indexing_options(title_indexing)
         .set_stored();
     let title = schema_builder.add_text_field("title", title_options);
 
+    // content 索引原始 Markdown (不做渲染)，公式与 Mermaid 源码同样可搜索
+    let content_indexing = TextFieldIndexing::default()
+        .set_tokenizer(CONTENT_TOKENIZER)
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
     let content_options = TextOptions::default()
-        .set_indexing_options(text_field_indexing)
+        .set_indexing_options(content_indexing)
         .set_stored();
     let content = schema_builder.add_text_field("content", content_options);
 
+    // 标题的拼音检索键 (见 pinyin_keys)，每个值作为一个完整词项，用于前缀匹配
+    let pinyin_indexing = TextFieldIndexing::default()
+        .set_tokenizer("raw")
+        .set_index_option(IndexRecordOption::Basic);
+    let title_pinyin = schema_builder.add_text_field(
+        "title_pinyin",
+        TextOptions::default().set_indexing_options(pinyin_indexing),
+    );
+    // 文件修改时间 (Unix 秒)
+    let modified = schema_builder.add_u64_field("modified", STORED | FAST);
+
     let schema = schema_builder.build();
     let fields = SchemaFields {
         id,
         path,
         title,
         content,
+        title_pinyin,
+        modified,
     };
     (schema, fields)
 }
 
-pub fn initialize_index(meta_dir: &Path) -> Result<Arc<Index>> {
-    let index_path = meta_dir.join(".cheetah_index");
+/// 标题字段的默认权重：标题 (含别名) 命中的笔记排在仅正文命中的笔记之前
+const TITLE_BOOST: f32 = 2.0;
+
+/// 搜索排序设置：文本相关度 (BM25) 乘以 1 + 各项加权
+/// 最近修改的加权按半衰期衰减，反向链接的加权按 ln(1 + 反向链接数) 增长
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    pub title_boost: f32, // 标题命中相对正文命中的权重
+    pub recency_boost: f32,
+    pub recency_half_life_days: f32,
+    pub pinned_boost: f32,
+    pub favorite_boost: f32,
+    pub backlink_boost: f32,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        RankingConfig {
+            title_boost: TITLE_BOOST,
+            recency_boost: 0.3,
+            recency_half_life_days: 30.0,
+            pinned_boost: 0.5,
+            favorite_boost: 0.3,
+            backlink_boost: 0.1,
+        }
+    }
+}
+
+/// 一个笔记的排序信号 (来自数据库)
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct NoteSignals {
+    pub pinned: bool,
+    pub favorited: bool,
+    pub backlinks: u32, // 链接到该笔记的其他笔记数
+}
+
+/// 排序加权的组成，multiplier = 1 + 各项之和
+#[derive(Debug, Clone, Serialize)]
+pub struct RankingBoost {
+    pub recency: f32,
+    pub pinned: f32,
+    pub favorite: f32,
+    pub backlinks: f32,
+    pub multiplier: f32,
+}
+
+impl RankingConfig {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let weights = [
+            ("title_boost", self.title_boost),
+            ("recency_boost", self.recency_boost),
+            ("pinned_boost", self.pinned_boost),
+            ("favorite_boost", self.favorite_boost),
+            ("backlink_boost", self.backlink_boost),
+        ];
+        if let Some((name, _)) = weights.iter().find(|(_, value)| !value.is_finite() || *value < 0.0) {
+            return Err(format!("排序权重必须是非负数: {}", name));
+        }
+        if self.title_boost == 0.0 {
+            return Err("标题权重必须大于 0".to_string());
+        }
+        if !self.recency_half_life_days.is_finite() || self.recency_half_life_days <= 0.0 {
+            return Err("最近修改的半衰期必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// modified 为文件修改时间 (Unix 秒，0 表示未知)，now 为当前时间
+    pub fn boost(&self, signals: NoteSignals, modified: u64, now: u64) -> RankingBoost {
+        let recency = if modified == 0 {
+            0.0
+        } else {
+            let age_days = now.saturating_sub(modified) as f32 / 86_400.0;
+            self.recency_boost * 0.5f32.powf(age_days / self.recency_half_life_days)
+        };
+        let pinned = if signals.pinned { self.pinned_boost } else { 0.0 };
+        let favorite = if signals.favorited { self.favorite_boost } else { 0.0 };
+        let backlinks = self.backlink_boost * (1.0 + signals.backlinks as f32).ln();
+        RankingBoost {
+            recency,
+            pinned,
+            favorite,
+            backlinks,
+            multiplier: 1.0 + recency + pinned + favorite + backlinks,
+        }
+    }
+}
+
+/// 所有笔记的排序信号，按笔记 id 索引 (只记录有信号的笔记)
+/// 置顶、收藏和链接变化时不需要重建索引，因此在搜索时从数据库读取
+#[derive(Debug, Clone, Default)]
+pub struct RankingSignals {
+    notes: HashMap<u64, NoteSignals>,
+}
+
+impl RankingSignals {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let mut stmt = conn.prepare(
+            "SELECT f.id, COALESCE(f.is_pinned, 0), COALESCE(f.is_favorited, 0),
+                    (SELECT COUNT(DISTINCT l.source_file_id) FROM links l
+                     WHERE l.target_file_id = f.id AND l.source_file_id != f.id)
+             FROM files f WHERE f.is_dir = 0",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)? as u64,
+                NoteSignals {
+                    pinned: row.get::<_, i64>(1)? != 0,
+                    favorited: row.get::<_, i64>(2)? != 0,
+                    backlinks: row.get::<_, i64>(3)? as u32,
+                },
+            ))
+        })?;
+        let mut notes = HashMap::new();
+        for row in rows {
+            let (id, signals) = row?;
+            if signals.pinned || signals.favorited || signals.backlinks > 0 {
+                notes.insert(id, signals);
+            }
+        }
+        Ok(RankingSignals { notes })
+    }
+
+    pub fn get(&self, id: Option<u64>) -> NoteSignals {
+        id.and_then(|id| self.notes.get(&id).copied()).unwrap_or_default()
+    }
+}
+
+/// 读取排序信号失败时不影响搜索，只是不加权
+fn load_ranking_signals(conn: &Connection) -> RankingSignals {
+    RankingSignals::load(conn).unwrap_or_else(|e| {
+        println!("⚠️ [搜索] 读取排序信号失败: {}，不按置顶、收藏和反向链接加权", e);
+        RankingSignals::default()
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn file_modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 按排序设置调整得分后取前 limit 个文档：每个段读取 id 与 modified 快速字段，得分乘以排序加权
+fn ranked_top_docs(
+    searcher: &Searcher,
+    query: &dyn Query,
+    ranking: &RankingConfig,
+    signals: &Arc<RankingSignals>,
+    limit: usize,
+) -> Result<Vec<(Score, DocAddress)>> {
+    let ranking = ranking.clone();
+    let signals = signals.clone();
+    let now = unix_now();
+    let collector = TopDocs::with_limit(limit).tweak_score(move |segment_reader: &SegmentReader| {
+        let ids = segment_reader.fast_fields().u64("id").ok();
+        let modified = segment_reader.fast_fields().u64("modified").ok();
+        let ranking = ranking.clone();
+        let signals = signals.clone();
+        move |doc: DocId, score: Score| {
+            let id = ids.as_ref().and_then(|column| column.first(doc));
+            let modified = modified.as_ref().and_then(|column| column.first(doc)).unwrap_or(0);
+            score * ranking.boost(signals.get(id), modified, now).multiplier
+        }
+    });
+    Ok(searcher.search(query, &collector)?)
+}
+
+/// 拼音检索键的最大字数 (超长标题只取前面部分)
+const MAX_PINYIN_UNITS: usize = 32;
+
+/// 标题按拼音切分的单元：(全拼, 首字母, 在标题中的字符下标范围)
+/// 汉字为一个单元，连续的英文字母数字为一个单元 (全拼为小写单词，首字母为其第一个字母)
+/// 标题中没有汉字时返回 None
+fn pinyin_units(title: &str) -> Option<Vec<(String, String, Range<usize>)>> {
+    let mut units: Vec<(String, String, Range<usize>)> = Vec::new();
+    let mut has_han = false;
+    let mut word = String::new();
+    let mut word_start = 0;
+    let flush = |word: &mut String, start: usize, units: &mut Vec<(String, String, Range<usize>)>| {
+        if !word.is_empty() {
+            let initial = word[..1].to_string();
+            let end = start + word.len();
+            units.push((std::mem::take(word), initial, start..end));
+        }
+    };
+    for (index, c) in title.chars().enumerate() {
+        if let Some(pinyin) = c.to_pinyin() {
+            flush(&mut word, word_start, &mut units);
+            units.push((pinyin.plain().to_string(), pinyin.first_letter().to_string(), index..index + 1));
+            has_han = true;
+        } else if c.is_ascii_alphanumeric() {
+            if word.is_empty() {
+                word_start = index;
+            }
+            word.push(c.to_ascii_lowercase());
+        } else {
+            flush(&mut word, word_start, &mut units);
+        }
+    }
+    flush(&mut word, word_start, &mut units);
+    if !has_han {
+        return None;
+    }
+    units.truncate(MAX_PINYIN_UNITS);
+    Some(units)
+}
+
+/// 标题的拼音形式：全拼或首字母串，以及其中每个字母对应的标题字符下标 (用于高亮)
+pub struct PinyinForm {
+    pub text: String,
+    pub char_indices: Vec<usize>,
+}
+
+/// 标题的整句全拼与首字母 (如 "开发日志" -> "kaifarizhi"、"kfrz")，标题中没有汉字时返回空
+pub fn pinyin_forms(title: &str) -> Vec<PinyinForm> {
+    let Some(units) = pinyin_units(title) else { return Vec::new() };
+    let mut full = PinyinForm { text: String::new(), char_indices: Vec::new() };
+    let mut initials = PinyinForm { text: String::new(), char_indices: Vec::new() };
+    for (pinyin, initial, range) in &units {
+        // 英文单词的每个字母对应自身，汉字的所有拼音字母对应该汉字
+        for (i, c) in pinyin.chars().enumerate() {
+            full.text.push(c);
+            full.char_indices.push(if range.len() > 1 { range.start + i } else { range.start });
+        }
+        initials.text.push_str(initial);
+        initials.char_indices.push(range.start);
+    }
+    vec![full, initials]
+}
+
+/// 标题的拼音检索键：全拼与首字母 (英文单词取整个单词 / 首字母)，
+/// 并且从每个字开始的后缀都作为一个键，使 "kaifa"、"kfrz"、"rizhi" 都能前缀匹配到 "开发日志"
+/// 标题中没有汉字时返回空
+pub fn pinyin_keys(title: &str) -> Vec<String> {
+    let Some(units) = pinyin_units(title) else { return Vec::new() };
+    let mut keys: Vec<String> = Vec::new();
+    for start in 0..units.len() {
+        let full: String = units[start..].iter().map(|(full, _, _)| full.as_str()).collect();
+        let initials: String = units[start..].iter().map(|(_, initial, _)| initial.as_str()).collect();
+        keys.push(full);
+        keys.push(initials);
+    }
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// 拼音查询：查询由字母数字组成 (可含空格) 时，前缀匹配标题的拼音检索键
+fn pinyin_query(fields: &SchemaFields, query: &str, title_boost: f32) -> Option<Box<dyn Query>> {
+    let key: String = query.split_whitespace().collect::<String>().to_lowercase();
+    if key.len() < 2 || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let query = RegexQuery::from_pattern(&format!("{}.*", key), fields.title_pinyin).ok()?;
+    Some(Box::new(BoostQuery::new(Box::new(query), title_boost)))
+}
+
+/// 模糊查询的最短词长，更短的词容错匹配意义不大
+const FUZZY_MIN_TERM_CHARS: usize = 3;
+/// 超过该长度的词允许 2 处编辑，否则 1 处
+const FUZZY_LONG_TERM_CHARS: usize = 6;
+
+/// 每个词最多展开的相近词项数 (编辑距离小、出现在更多笔记中的优先)
+const FUZZY_MAX_EXPANSIONS: usize = 20;
+
+/// 编辑距离 (相邻字符换位计 1 次，与 FuzzyTermQuery 一致)，超过 max 时返回 None
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (previous[j] + 1).min(row[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        if row.iter().all(|&d| d > max) {
+            return None;
+        }
+        before_previous = std::mem::replace(&mut previous, row);
+    }
+    Some(previous[b.len()]).filter(|&d| d <= max)
+}
+
+/// 在字段的词典中查找与 word 编辑距离不超过 distance 的词项
+fn fuzzy_terms(searcher: &Searcher, field: Field, word: &str, distance: usize) -> Result<Vec<Term>> {
+    let word: Vec<char> = word.chars().collect();
+    let mut found: HashMap<String, usize> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut stream = inverted_index.terms().stream()?;
+        while stream.advance() {
+            let Ok(text) = std::str::from_utf8(stream.key()) else { continue };
+            if found.contains_key(text) || text.chars().count().abs_diff(word.len()) > distance {
+                continue;
+            }
+            let candidate: Vec<char> = text.chars().collect();
+            if let Some(d) = edit_distance(&word, &candidate, distance) {
+                found.insert(text.to_string(), d);
+            }
+        }
+    }
+    let mut terms: Vec<(usize, u64, Term)> = Vec::new();
+    for (text, d) in found {
+        let term = Term::from_field_text(field, &text);
+        terms.push((d, searcher.doc_freq(&term)?, term));
+    }
+    terms.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    terms.truncate(FUZZY_MAX_EXPANSIONS);
+    Ok(terms.into_iter().map(|(_, _, term)| term).collect())
+}
+
+/// 容错查询：查询中的每个拉丁字母词按编辑距离 (1~2) 匹配标题和正文
+/// 中文词不做模糊匹配；只有查询全部为普通文本条件时才做容错搜索，
+/// 排除条件和 tag: / path: / modified: 不能放宽，否则会返回被排除或不在范围内的笔记
+/// 每个词展开为索引中实际存在的相近词项，摘要和高亮因此能标出匹配到的词
+fn fuzzy_query(
+    searcher: &Searcher,
+    index: &Index,
+    fields: &SchemaFields,
+    query: &str,
+    title_boost: f32,
+) -> Result<Option<Box<dyn Query>>> {
+    let Some(texts) = parse_search_query(query).ok().and_then(|parsed| parsed.plain_texts()) else {
+        return Ok(None);
+    };
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for text in &texts {
+        for (field, enabled) in [(fields.title, text.title), (fields.content, text.content)] {
+            if !enabled {
+                continue;
+            }
+            let mut words: Vec<String> = Vec::new();
+            let mut analyzer = index.tokenizer_for_field(field)?;
+            analyzer.token_stream(&text.text).process(&mut |token| {
+                let chars = token.text.chars().count();
+                if chars >= FUZZY_MIN_TERM_CHARS && token.text.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    words.push(token.text.clone());
+                }
+            });
+            let boost = if field == fields.title { title_boost } else { 1.0 };
+            for word in words {
+                let distance = if word.len() >= FUZZY_LONG_TERM_CHARS { 2 } else { 1 };
+                for term in fuzzy_terms(searcher, field, &word, distance)? {
+                    let term_query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+                    clauses.push((Occur::Should, Box::new(BoostQuery::new(term_query, boost))));
+                }
+            }
+        }
+    }
+    Ok((!clauses.is_empty()).then(|| Box::new(BooleanQuery::new(clauses)) as Box<dyn Query>))
+}
+
+/// 构建笔记文档：title 字段第一个值为标题 (frontmatter 的 title 优先于文件名)，
+/// 其后为 frontmatter 中的别名，使别名同样按标题权重参与搜索
+fn build_note_document(fields: &SchemaFields, id: i64, relative_path: &str, content: String, modified: u64) -> TantivyDocument {
+    let title = note_title(relative_path, &content);
+    let mut titles = vec![title.clone()];
+    let mut document = doc!(
+        fields.id => id as u64,
+        fields.path => relative_path.to_string(),
+        fields.title => title,
+        fields.modified => modified
+    );
+    if let Some(mapping) = parse_frontmatter(&content) {
+        for alias in frontmatter_aliases(&mapping) {
+            document.add_text(fields.title, &alias);
+            titles.push(alias);
+        }
+    }
+    for title in &titles {
+        for key in pinyin_keys(title) {
+            document.add_text(fields.title_pinyin, key);
+        }
+    }
+    document.add_text(fields.content, content);
+    document
+}
+
+/// 索引所在目录
+pub fn index_path(meta_dir: &Path) -> PathBuf {
+    meta_dir.join(".cheetah_index")
+}
+
+/// 应用分词配置：注册分词器，配置与建立索引时不同时清空索引
+/// 返回 true 表示索引已被清空，调用方需要重新索引所有笔记
+pub fn apply_analyzer_config(index: &Index, index_path: &Path, config: &AnalyzerConfig) -> Result<bool> {
+    register_analyzers(index, config);
+
+    let config_path = index_path.join(ANALYZER_CONFIG_FILE);
+    let current = StoredAnalyzerConfig::current(config);
+    let stored: Option<StoredAnalyzerConfig> = fs::read_to_string(&config_path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    if stored.as_ref() == Some(&current) {
+        return Ok(false);
+    }
+
+    let num_docs = index.reader()?.searcher().num_docs();
+    if num_docs > 0 {
+        println!("🔀 [索引] 分词配置已变化 ({:?} -> {:?})，清空索引...", stored, current);
+        let mut writer: IndexWriter = index.writer(20_000_000)?;
+        writer.delete_all_documents()?;
+        writer.commit()?;
+    }
+    fs::write(&config_path, serde_json::to_string(&current)?)?;
+    Ok(num_docs > 0)
+}
+
+/// 打开索引；索引结构 (schema) 与当前版本不一致时删除旧索引并重新创建，
+/// 分词配置变化时清空索引
+/// 返回的 bool 表示索引被重建 (此时为空)，调用方需要重新索引所有笔记
+pub fn initialize_index(meta_dir: &Path, config: &AnalyzerConfig) -> Result<(Arc<Index>, bool)> {
+    let index_path = index_path(meta_dir);
     if !index_path.exists() {
         fs::create_dir_all(&index_path)?;
     }
     let (schema, _) = build_schema();
     let dir = MmapDirectory::open(&index_path)?;
-    let index = Index::open_or_create(dir, schema.clone())?;
-    let analyzer = TextAnalyzer::builder(JIEBA_TOKENIZER.clone())
-        .filter(RemoveLongFilter::limit(40))
-        .filter(LowerCaser)
-        .build();
-    index.tokenizers().register("jieba", analyzer);
-    Ok(Arc::new(index))
+    let (index, rebuilt) = match Index::open_or_create(dir, schema.clone()) {
+        Ok(index) => (index, false),
+        Err(TantivyError::SchemaError(e)) => {
+            println!("🔀 [索引] 索引结构已变化 ({})，重建索引...", e);
+            fs::remove_dir_all(&index_path)?;
+            fs::create_dir_all(&index_path)?;
+            (Index::create_in_dir(&index_path, schema)?, true)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let cleared = apply_analyzer_config(&index, &index_path, config)?;
+    Ok((Arc::new(index), rebuilt || cleared))
 }
 
 
@@ -99,16 +673,9 @@ pub fn index_documents(
     for file_result in file_iter {
         let (id, relative_path_str) = file_result?;
         let absolute_path = to_absolute_path(base_path, Path::new(&relative_path_str));
-        let content = fs::read_to_string(absolute_path).unwrap_or_default();
-        // [修正] 使用正确的函数名
-        let title = extract_title_from_path(&relative_path_str);
-
-        index_writer.add_document(doc!(
-            fields.id => id as u64,
-            fields.path => relative_path_str,
-            fields.title => title,
-            fields.content => content
-        ))?;
+        let content = fs::read_to_string(&absolute_path).unwrap_or_default();
+        let modified = file_modified_secs(&absolute_path);
+        index_writer.add_document(build_note_document(&fields, id, &relative_path_str, content, modified))?;
     }
     index_writer.commit()?;
     Ok(())
@@ -128,8 +695,6 @@ pub fn update_document_index(
     let content = fs::read_to_string(&absolute_path)
         .with_context(|| format!("读取文件失败: {}", absolute_path.display()))?;
     let relative_path_str = relative_path.to_string_lossy().to_string();
-    // [修正] 使用正确的函数名
-    let title = extract_title_from_path(&relative_path_str);
     let file_id: i64 = conn.query_row(
         "SELECT id FROM files WHERE path = ?1",
         params![relative_path_str],
@@ -137,11 +702,12 @@ pub fn update_document_index(
     )?;
     let path_term = tantivy::Term::from_field_text(fields.path, &relative_path_str);
     writer.delete_term(path_term);
-    writer.add_document(doc!(
-        fields.id => file_id as u64,
-        fields.path => relative_path_str.clone(),
-        fields.title => title,
-        fields.content => content
+    writer.add_document(build_note_document(
+        &fields,
+        file_id,
+        &relative_path_str,
+        content,
+        file_modified_secs(&absolute_path),
     ))?;
     writer.commit()?;
     Ok(())
@@ -163,8 +729,6 @@ pub fn update_document_index_for_rename(
         .with_context(|| format!("读取文件失败: {}", absolute_path.display()))?;
     let relative_path_str_old = relative_path_old.to_string_lossy().to_string();
 	let relative_path_str_new = relative_path_new.to_string_lossy().to_string();
-    // [修正] 使用正确的函数名
-    let title = extract_title_from_path(&relative_path_str_new);
 	 println!("🔍 [索引] 查询fileid'，用路径relative_path_str_new: {}", relative_path_str_new);
     let file_id: i64 = conn.query_row(
         "SELECT id FROM files WHERE path = ?1",
@@ -173,17 +737,69 @@ pub fn update_document_index_for_rename(
     )?;
     let path_term = tantivy::Term::from_field_text(fields.path, &relative_path_str_old);
     writer.delete_term(path_term);
-    writer.add_document(doc!(
-        fields.id => file_id as u64,
-        fields.path => relative_path_str_new.clone(),
-        fields.title => title,
-        fields.content => content
+    writer.add_document(build_note_document(
+        &fields,
+        file_id,
+        &relative_path_str_new,
+        content,
+        file_modified_secs(&absolute_path),
     ))?;
     writer.commit()?;
     Ok(())
 }
 
-pub fn search(index: &Index, query: &str) -> Result<Vec<SearchResult>> {
+/// 解析搜索语句为 tantivy 查询 (语法错误时按普通文本解析)，并加上标题拼音查询
+/// 返回 None 表示查询不会匹配任何笔记
+fn build_search_query(
+    index: &Index,
+    fields: &SchemaFields,
+    query: &str,
+    conn: Option<&Connection>,
+    ranking: &RankingConfig,
+) -> Result<Option<Box<dyn Query>>> {
+    let parsed_query = match parse_search_query(query) {
+        Ok(parsed) => {
+            let ctx = QueryContext { index, fields, conn, title_boost: ranking.title_boost };
+            match parsed.to_query(&ctx)? {
+                Some(q) => q,
+                None => return Ok(None),
+            }
+        }
+        Err(e) => {
+            println!("⚠️ [搜索] {}，按普通文本搜索", e);
+            let mut query_parser = QueryParser::for_index(index, vec![fields.title, fields.content]);
+            query_parser.set_field_boost(fields.title, ranking.title_boost);
+            query_parser.parse_query_lenient(query).0
+        }
+    };
+    // 字母查询同时按标题拼音匹配 (如 kfrz -> 开发日志)
+    Ok(Some(match pinyin_query(fields, query, ranking.title_boost) {
+        Some(pinyin) => Box::new(BooleanQuery::new(vec![
+            (Occur::Should, parsed_query),
+            (Occur::Should, pinyin),
+        ])),
+        None => parsed_query,
+    }))
+}
+
+/// 搜索框返回的结果数
+const SEARCH_RESULT_LIMIT: usize = 10;
+
+/// 全文搜索，查询语法见 search_query；语法错误时按普通文本搜索
+/// conn 用于 tag: / path: / modified: 字段及置顶、收藏、反向链接的排序加权
+pub fn search(index: &Index, query: &str, conn: Option<&Connection>, ranking: &RankingConfig) -> Result<Vec<SearchResult>> {
+    search_top(index, query, conn, ranking, SEARCH_RESULT_LIMIT, true)
+}
+
+/// 同 search，最多返回 limit 个结果；fuzzy_fallback 为 false 时只返回精确匹配 (如保存的搜索)
+pub fn search_top(
+    index: &Index,
+    query: &str,
+    conn: Option<&Connection>,
+    ranking: &RankingConfig,
+    limit: usize,
+    fuzzy_fallback: bool,
+) -> Result<Vec<SearchResult>> {
     if query.trim().is_empty() {
         return Ok(Vec::new());
     }
@@ -194,15 +810,31 @@ pub fn search(index: &Index, query: &str) -> Result<Vec<SearchResult>> {
         .try_into()?;
     let searcher = reader.searcher();
 
-    let query_parser = QueryParser::for_index(index, vec![fields.title, fields.content]);
-    let parsed_query = query_parser.parse_query(query)?;
-    let top_docs = searcher.search(&parsed_query, &TopDocs::with_limit(10))?;
+    let Some(mut matched_query) = build_search_query(index, &fields, query, conn, ranking)? else {
+        return Ok(Vec::new());
+    };
+    let signals = Arc::new(conn.map(load_ranking_signals).unwrap_or_default());
+    let mut top_docs = ranked_top_docs(&searcher, matched_query.as_ref(), ranking, &signals, limit)?;
+
+    // 精确搜索无结果时退回容错搜索 (如 serach -> search)，摘要和高亮改用容错查询生成
+    let mut fuzzy = false;
+    if top_docs.is_empty() && fuzzy_fallback {
+        if let Some(fuzzy_query) = fuzzy_query(&searcher, index, &fields, query, ranking.title_boost)? {
+            top_docs = ranked_top_docs(&searcher, fuzzy_query.as_ref(), ranking, &signals, limit)?;
+            fuzzy = !top_docs.is_empty();
+            matched_query = fuzzy_query;
+        }
+    }
 
-    let mut snippet_generator = SnippetGenerator::create(&searcher, &parsed_query, fields.content)?;
+    let mut snippet_generator = SnippetGenerator::create(&searcher, matched_query.as_ref(), fields.content)?;
     snippet_generator.set_max_num_chars(120);
+    let content_terms = highlight_terms(&searcher, matched_query.as_ref(), fields.content)?;
+    let title_terms = highlight_terms(&searcher, matched_query.as_ref(), fields.title)?;
+    let mut content_analyzer = index.tokenizer_for_field(fields.content)?;
+    let mut title_analyzer = index.tokenizer_for_field(fields.title)?;
 
     let mut results = Vec::new();
-    for (_score, doc_address) in top_docs {
+    for (score, doc_address) in top_docs {
         let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
         let path = retrieved_doc
             .get_first(fields.path)
@@ -214,8 +846,13 @@ pub fn search(index: &Index, query: &str) -> Result<Vec<SearchResult>> {
             continue;
         }
         
-        // [修正] 使用正确的函数名和变量名
-        let title = extract_title_from_path(&path);
+        // 优先使用索引中存储的标题 (可能来自 frontmatter)
+        let title = retrieved_doc
+            .get_first(fields.title)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| extract_title_from_path(&path));
 
         let snippet = snippet_generator.snippet_from_doc(&retrieved_doc);
         let snippet_html = snippet
@@ -223,12 +860,503 @@ pub fn search(index: &Index, query: &str) -> Result<Vec<SearchResult>> {
             .replace("<b>", "<mark>")
             .replace("</b>", "</mark>");
 
+        let content = retrieved_doc.get_first(fields.content).and_then(|v| v.as_str()).unwrap_or("");
+        let snippets = content_snippets(content, &highlight_ranges(&mut content_analyzer, content, &content_terms));
+        let title_highlights: Vec<Range<usize>> = highlight_ranges(&mut title_analyzer, &title, &title_terms)
+            .into_iter()
+            .map(|(range, _)| range)
+            .collect();
+        let (title_html, title_ranges) = mark_text(&title, &title_highlights);
+
         results.push(SearchResult {
             path,
             title,
+            title_html,
+            title_ranges,
             snippet: snippet_html,
+            snippets,
+            score,
+            fuzzy,
+        });
+    }
+    Ok(results)
+}
+
+/// 某个笔记在一次搜索中的得分构成
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchExplanation {
+    pub path: String,
+    pub score: f32,      // 最终得分 = text_score × boost.multiplier
+    pub text_score: f32, // 文本相关度 (BM25，含字段权重)
+    pub fuzzy: bool,     // 按容错查询匹配
+    pub modified: u64,
+    pub signals: NoteSignals,
+    pub boost: RankingBoost,
+    pub explanation: String, // tantivy 给出的文本相关度计算过程 (JSON)
+}
+
+/// 解释某个笔记的搜索得分；笔记不在索引中或不匹配查询时返回 None
+pub fn explain_search(
+    index: &Index,
+    query: &str,
+    path: &str,
+    conn: Option<&Connection>,
+    ranking: &RankingConfig,
+) -> Result<Option<SearchExplanation>> {
+    if query.trim().is_empty() {
+        return Ok(None);
+    }
+    let (_, fields) = build_schema();
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let path_query = TermQuery::new(Term::from_field_text(fields.path, path), IndexRecordOption::Basic);
+    let Some((_, doc_address)) = searcher.search(&path_query, &TopDocs::with_limit(1))?.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let mut candidates: Vec<(Box<dyn Query>, bool)> = Vec::new();
+    if let Some(parsed_query) = build_search_query(index, &fields, query, conn, ranking)? {
+        candidates.push((parsed_query, false));
+    }
+    if let Some(fuzzy_query) = fuzzy_query(&searcher, index, &fields, query, ranking.title_boost)? {
+        candidates.push((fuzzy_query, true));
+    }
+    // 不匹配时 explain 返回错误，依次尝试精确查询与容错查询
+    let Some((explanation, fuzzy)) = candidates
+        .iter()
+        .find_map(|(q, fuzzy)| q.explain(&searcher, doc_address).ok().map(|e| (e, *fuzzy)))
+    else {
+        return Ok(None);
+    };
+
+    let doc: TantivyDocument = searcher.doc(doc_address)?;
+    let id = doc.get_first(fields.id).and_then(|v| v.as_u64());
+    let modified = doc.get_first(fields.modified).and_then(|v| v.as_u64()).unwrap_or(0);
+    let signals = conn.map(load_ranking_signals).unwrap_or_default().get(id);
+    let boost = ranking.boost(signals, modified, unix_now());
+    let text_score = explanation.value();
+    Ok(Some(SearchExplanation {
+        path: path.to_string(),
+        score: text_score * boost.multiplier,
+        text_score,
+        fuzzy,
+        modified,
+        signals,
+        boost,
+        explanation: explanation.to_pretty_json(),
+    }))
+}
+
+/// 每个结果最多返回的摘要数
+const MAX_SNIPPETS: usize = 3;
+/// 摘要的最大字符数
+const SNIPPET_CHARS: usize = 160;
+/// 摘要中第一个命中之前最多保留的字符数
+const SNIPPET_LEAD_CHARS: usize = 40;
+
+/// 查询在某个字段上的词项及权重 (越少见的词权重越高，与 tantivy 生成摘要时的算法一致)
+fn highlight_terms(searcher: &Searcher, query: &dyn Query, field: Field) -> Result<HashMap<String, f32>> {
+    let mut terms: Vec<Term> = Vec::new();
+    query.query_terms(&mut |term, _| {
+        if term.field() == field {
+            terms.push(term.clone());
+        }
+    });
+    let mut weights = HashMap::new();
+    for term in terms {
+        let value = term.value();
+        let Some(text) = value.as_str() else { continue };
+        let doc_freq = searcher.doc_freq(&term)?;
+        if doc_freq > 0 {
+            weights.insert(text.to_string(), 1.0 / (1.0 + doc_freq as f32));
+        }
+    }
+    Ok(weights)
+}
+
+/// 用字段的分词器切分文本，返回命中查询词项的字节范围 (已排序，重叠部分合并) 及权重
+fn highlight_ranges(analyzer: &mut TextAnalyzer, text: &str, terms: &HashMap<String, f32>) -> Vec<(Range<usize>, f32)> {
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut ranges: Vec<(Range<usize>, f32)> = Vec::new();
+    analyzer.token_stream(text).process(&mut |token| {
+        if let Some(weight) = terms.get(&token.text) {
+            ranges.push((token.offset_from..token.offset_to, *weight));
+        }
+    });
+    ranges.sort_by_key(|(range, _)| (range.start, range.end));
+    let mut merged: Vec<(Range<usize>, f32)> = Vec::new();
+    for (range, weight) in ranges {
+        match merged.last_mut() {
+            Some((last, last_weight)) if range.start <= last.end => {
+                last.end = last.end.max(range.end);
+                *last_weight = last_weight.max(weight);
+            }
+            _ => merged.push((range, weight)),
+        }
+    }
+    merged
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// 用 <mark> 标出文本中的命中 (字节范围)，同时返回命中的 UTF-16 范围
+fn mark_text(text: &str, ranges: &[Range<usize>]) -> (String, Vec<(usize, usize)>) {
+    let mut html = String::with_capacity(text.len());
+    let mut utf16_ranges = Vec::with_capacity(ranges.len());
+    let mut last = 0;
+    for range in ranges {
+        html.push_str(&escape_html(&text[last..range.start]));
+        html.push_str("<mark>");
+        html.push_str(&escape_html(&text[range.clone()]));
+        html.push_str("</mark>");
+        let start = text[..range.start].encode_utf16().count();
+        utf16_ranges.push((start, start + text[range.clone()].encode_utf16().count()));
+        last = range.end;
+    }
+    html.push_str(&escape_html(&text[last..]));
+    (html, utf16_ranges)
+}
+
+/// 取命中权重之和最高的几行作为摘要，按行号排列；过长的行截取第一个命中附近的一段
+fn content_snippets(content: &str, highlights: &[(Range<usize>, f32)]) -> Vec<SearchSnippet> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut lines: Vec<(usize, f32, Vec<Range<usize>>)> = Vec::new(); // (行号, 得分, 命中)
+    for (range, weight) in highlights {
+        let line = line_starts.partition_point(|&s| s <= range.start) - 1;
+        match lines.last_mut() {
+            Some((last_line, score, ranges)) if *last_line == line => {
+                *score += weight;
+                ranges.push(range.clone());
+            }
+            _ => lines.push((line, *weight, vec![range.clone()])),
+        }
+    }
+    lines.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    lines.truncate(MAX_SNIPPETS);
+    lines.sort_by_key(|(line, _, _)| *line);
+
+    lines
+        .into_iter()
+        .map(|(line, _, ranges)| {
+            let start = line_starts[line];
+            let end = line_starts.get(line + 1).map(|e| e - 1).unwrap_or(content.len());
+            let text = content[start..end].trim_end_matches('\r');
+            let local: Vec<Range<usize>> = ranges
+                .iter()
+                .map(|r| (r.start - start).min(text.len())..(r.end - start).min(text.len()))
+                .collect();
+            let first = local[0].start;
+            let clip_start = text[..first]
+                .char_indices()
+                .rev()
+                .nth(SNIPPET_LEAD_CHARS - 1)
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            let clip_end = text[clip_start..]
+                .char_indices()
+                .nth(SNIPPET_CHARS)
+                .map(|(i, _)| clip_start + i)
+                .unwrap_or(text.len());
+            let visible: Vec<Range<usize>> = local
+                .iter()
+                .filter(|r| r.start >= clip_start && r.end <= clip_end && r.start < r.end)
+                .map(|r| r.start - clip_start..r.end - clip_start)
+                .collect();
+            let prefix = if clip_start > 0 { "…" } else { "" };
+            let suffix = if clip_end < text.len() { "…" } else { "" };
+            let fragment = &text[clip_start..clip_end];
+            let (html, ranges) = mark_text(fragment, &visible);
+            let shift = prefix.encode_utf16().count();
+            SearchSnippet {
+                line: line + 1,
+                column: text[..first].encode_utf16().count() + 1,
+                text: format!("{}{}{}", prefix, fragment, suffix),
+                html: format!("{}{}{}", prefix, html, suffix),
+                ranges: ranges.into_iter().map(|(s, e)| (s + shift, e + shift)).collect(),
+            }
+        })
+        .collect()
+}
+
+/// 查找正文中包含任一短语的笔记 (按分词后的短语匹配)，返回 (路径, 标题, 正文)
+/// 分词结果与索引一致，因此大小写与中文分词的差异不影响召回，精确位置由调用方在正文中确定
+pub fn find_phrase_matches(index: &Index, phrases: &[String], limit: usize) -> Result<Vec<(String, String, String)>> {
+    let (_, fields) = build_schema();
+    let mut analyzer = index.tokenizer_for_field(fields.content)?;
+    let mut queries: Vec<Box<dyn Query>> = Vec::new();
+    for phrase in phrases {
+        let mut terms: Vec<(usize, Term)> = Vec::new();
+        analyzer.token_stream(phrase).process(&mut |token| {
+            terms.push((token.position, Term::from_field_text(fields.content, &token.text)));
+        });
+        match terms.len() {
+            0 => {}
+            1 => queries.push(Box::new(TermQuery::new(terms.remove(0).1, IndexRecordOption::Basic))),
+            _ => queries.push(Box::new(PhraseQuery::new_with_offset(terms))),
+        }
+    }
+    if queries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let top_docs = searcher.search(&BooleanQuery::union(queries), &TopDocs::with_limit(limit))?;
+
+    let mut results = Vec::new();
+    for (_score, doc_address) in top_docs {
+        let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+        let get_text = |field: Field| {
+            retrieved_doc
+                .get_first(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let path = get_text(fields.path);
+        if path.is_empty() {
+            continue;
+        }
+        results.push((path, get_text(fields.title), get_text(fields.content)));
+    }
+    Ok(results)
+}
+
+/// 字面文本搜索的候选笔记：取文本内部完整的英文/数字词 (两侧都不与其它字母数字相连)，
+/// 要求笔记正文包含所有这些词；没有可用的词时返回 None，表示需要扫描所有笔记
+/// 文本开头和结尾的词可能只是某个词的一部分，不能用于过滤
+pub fn literal_candidates(index: &Index, literal: &str) -> Result<Option<Vec<String>>> {
+    let (_, fields) = build_schema();
+    let chars: Vec<char> = literal.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_alphanumeric() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+            i += 1;
+        }
+        if start > 0 && i < chars.len() {
+            words.push(chars[start..i].iter().collect());
+        }
+    }
+
+    let mut analyzer = index.tokenizer_for_field(fields.content)?;
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for word in words {
+        analyzer.token_stream(&word).process(&mut |token| {
+            let term = Term::from_field_text(fields.content, &token.text);
+            clauses.push((Occur::Must, Box::new(TermQuery::new(term, IndexRecordOption::Basic))));
+        });
+    }
+    if clauses.is_empty() {
+        return Ok(None);
+    }
+
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let limit = (searcher.num_docs() as usize).max(1);
+    let top_docs = searcher.search(&BooleanQuery::new(clauses), &TopDocs::with_limit(limit))?;
+    let mut paths = Vec::with_capacity(top_docs.len());
+    for (_score, doc_address) in top_docs {
+        let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+        if let Some(path) = retrieved_doc.get_first(fields.path).and_then(|v| v.as_str()) {
+            paths.push(path.to_string());
+        }
+    }
+    Ok(Some(paths))
+}
+
+/// 按标题 (含别名) 搜索笔记，同时匹配标题拼音，用于快速打开
+/// 返回 (路径, 标题, 分数)
+pub fn search_titles(index: &Index, query: &str, limit: usize) -> Result<Vec<(String, String, f32)>> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let (_, fields) = build_schema();
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .try_into()?;
+    let searcher = reader.searcher();
+
+    let query_parser = QueryParser::for_index(index, vec![fields.title]);
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Should, query_parser.parse_query_lenient(query).0)];
+    if let Some(pinyin) = pinyin_query(&fields, query, TITLE_BOOST) {
+        clauses.push((Occur::Should, pinyin));
+    }
+    let top_docs = searcher.search(&BooleanQuery::new(clauses), &TopDocs::with_limit(limit))?;
+
+    let mut results = Vec::new();
+    for (score, doc_address) in top_docs {
+        let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+        let path = retrieved_doc
+            .get_first(fields.path)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        if path.is_empty() {
+            continue;
+        }
+        let title = retrieved_doc
+            .get_first(fields.title)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| extract_title_from_path(&path));
+        results.push((path, title, score));
+    }
+    Ok(results)
+}
+
+/// 相似笔记 (MoreLikeThis)
+#[derive(Debug, Clone)]
+pub struct SimilarNote {
+    pub path: String,
+    pub title: String,
+    pub score: f32,
+    pub shared_terms: Vec<String>, // 与源笔记共有的关键词
+}
+
+/// 每篇相似笔记最多列出的共有关键词数
+const MAX_SHARED_TERMS: usize = 5;
+/// 源笔记参与比较的关键词数
+const MAX_KEYWORDS: usize = 25;
+
+/// 是否为有意义的词 (至少两个字符且含有字母或数字，过滤标点和单字母)
+fn is_keyword_token(text: &str) -> bool {
+    text.chars().count() >= 2 && text.chars().any(char::is_alphanumeric)
+}
+
+fn tokenize_keywords(analyzer: &mut TextAnalyzer, text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    analyzer.token_stream(text).process(&mut |token| {
+        if is_keyword_token(&token.text) {
+            tokens.push(token.text.clone());
+        }
+    });
+    tokens
+}
+
+/// 索引词 (可能是词干) -> 原文中首次出现的词形，用于展示关键词
+/// 两个分词器的切分一致，按词的位置对应
+fn surface_forms(analyzer: &mut TextAnalyzer, text: &str) -> HashMap<String, String> {
+    let mut words: HashMap<usize, String> = HashMap::new();
+    build_analyzer(None, false).token_stream(text).process(&mut |token| {
+        words.insert(token.position, token.text.clone());
+    });
+    let mut forms: HashMap<String, String> = HashMap::new();
+    analyzer.token_stream(text).process(&mut |token| {
+        if let Some(word) = words.get(&token.position) {
+            forms.entry(token.text.clone()).or_insert_with(|| word.clone());
+        }
+    });
+    forms
+}
+
+/// 查找与 relative_path 内容相似的笔记 (不含自身)，并给出共有的关键词
+/// 关键词按 TF-IDF 从源笔记中选出
+pub fn find_similar_notes(index: &Index, relative_path: &str, limit: usize) -> Result<Vec<SimilarNote>> {
+    let (_, fields) = build_schema();
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::OnCommitWithDelay)
+        .try_into()?;
+    let searcher = reader.searcher();
+
+    let path_query = TermQuery::new(
+        Term::from_field_text(fields.path, relative_path),
+        IndexRecordOption::Basic,
+    );
+    let Some((_, source_address)) = searcher.search(&path_query, &TopDocs::with_limit(1))?.into_iter().next() else {
+        return Ok(Vec::new());
+    };
+    let source_doc: TantivyDocument = searcher.doc(source_address)?;
+    let stored_text = |doc: &TantivyDocument, field: Field| {
+        doc.get_first(field).and_then(|v| v.as_str()).unwrap_or("").to_string()
+    };
+    let source_content = stored_text(&source_doc, fields.content);
+    let source_title = stored_text(&source_doc, fields.title);
+
+    // 只用标题和正文构造查询 (path、id 只会匹配到自身)
+    let query = MoreLikeThisQuery::builder()
+        .with_min_doc_frequency(1)
+        .with_max_doc_frequency((searcher.num_docs() / 2).max(1))
+        .with_min_term_frequency(1)
+        .with_max_query_terms(MAX_KEYWORDS)
+        .with_min_word_length(2)
+        .with_document_fields(vec![
+            (fields.title, vec![OwnedValue::Str(source_title)]),
+            (fields.content, vec![OwnedValue::Str(source_content.clone())]),
+        ]);
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(limit + 1))?;
+
+    // 源笔记的关键词 (TF-IDF)
+    let mut analyzer = index.tokenizer_for_field(fields.content)?;
+    let mut term_freqs: HashMap<String, usize> = HashMap::new();
+    for token in tokenize_keywords(&mut analyzer, &source_content) {
+        *term_freqs.entry(token).or_default() += 1;
+    }
+    let num_docs = searcher.num_docs() as f64;
+    let mut keywords: Vec<(String, f64)> = Vec::with_capacity(term_freqs.len());
+    for (text, tf) in term_freqs {
+        let doc_freq = searcher.doc_freq(&Term::from_field_text(fields.content, &text))? as f64;
+        keywords.push((text, tf as f64 * (num_docs / (doc_freq + 1.0) + 1.0).ln()));
+    }
+    keywords.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    keywords.truncate(MAX_KEYWORDS);
+    let forms = surface_forms(&mut analyzer, &source_content);
+
+    let mut results = Vec::new();
+    for (score, doc_address) in top_docs {
+        if doc_address == source_address {
+            continue;
+        }
+        let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+        let path = stored_text(&retrieved_doc, fields.path);
+        if path.is_empty() {
+            continue;
+        }
+        let content = stored_text(&retrieved_doc, fields.content);
+        let candidate_terms: HashSet<String> = tokenize_keywords(&mut analyzer, &content).into_iter().collect();
+        let shared_terms = keywords
+            .iter()
+            .filter(|(text, _)| candidate_terms.contains(text))
+            .map(|(text, _)| forms.get(text).unwrap_or(text).clone())
+            .take(MAX_SHARED_TERMS)
+            .collect();
+        results.push(SimilarNote {
+            title: Some(stored_text(&retrieved_doc, fields.title))
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| extract_title_from_path(&path)),
+            path,
+            score,
+            shared_terms,
         });
     }
+    results.truncate(limit);
     Ok(results)
 }
 
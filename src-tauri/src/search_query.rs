@@ -0,0 +1,754 @@
+// src-tauri/src/search_query.rs
+// 全文搜索查询语法
+//
+// 语法:
+//   词语                  rust 所有权            多个条件之间默认为 AND (同时满足)
+//   "短语"                "borrow checker"       词语按顺序相邻出现
+//   字段:值               title:rust  content:"error handling"  title:(rust OR go)
+//                         其他 xxx: 前缀不是字段，按普通词语处理，如 TODO: fix
+//   tag:标签              tag:project            带有该标签或其子标签 (project/xxx) 的笔记
+//   path:路径             path:daily/  path:*.md  路径包含该文本，* 匹配任意字符 (不区分大小写)
+//   modified:日期         modified:2024-03  modified:>=2024-01-01  modified:2024-01..2024-03  modified:7d
+//   AND / OR / NOT        大写关键词，优先级 NOT > AND > OR
+//   -条件                 等同于 NOT 条件，如 -tag:archive
+//   ( )                   分组，如 (rust OR go) -java
+//
+// modified 的日期可写作 YYYY、YYYY-MM 或 YYYY-MM-DD (本地时间)，前面可加 > >= < <=；
+// a..b 表示从 a 到 b (含 b 整个时间段)；Nd / Nw 表示最近 N 天 / 周；today 表示今天
+//
+// 解析失败时返回带位置的 SearchQueryError，调用方可退回普通搜索并提示用户
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, Occur, PhraseQuery, Query, TermQuery, TermSetQuery,
+};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Index, Term};
+
+use crate::commands::query::paths_with_tag;
+use crate::commands::utils::escape_like;
+use crate::search_core::SchemaFields;
+
+/// 搜索语法错误，start / end 为出错位置在查询中的字符偏移 [start, end)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchQueryError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl std::fmt::Display for SearchQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "搜索语法错误 (位置 {}): {}", self.start + 1, self.message)
+    }
+}
+
+fn syntax_error(start: usize, end: usize, message: impl Into<String>) -> SearchQueryError {
+    SearchQueryError { message: message.into(), start, end: end.max(start + 1) }
+}
+
+// ============================================================================
+// 1. 词法分析
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Phrase(String),
+    Field(String),
+    Minus,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+const FIELD_NAMES: &[&str] = &["title", "content", "tag", "path", "modified"];
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '"')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, SearchQueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token { kind: TokenKind::LParen, start, end: i + 1 });
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token { kind: TokenKind::RParen, start, end: i + 1 });
+                i += 1;
+            }
+            '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error(start, chars.len(), "短语缺少结束引号 '\"'")),
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token { kind: TokenKind::Phrase(value), start, end: i });
+            }
+            '-' if chars.get(i + 1).is_some_and(|&n| is_word_char(n) || n == '(' || n == '"') => {
+                tokens.push(Token { kind: TokenKind::Minus, start, end: i + 1 });
+                i += 1;
+            }
+            _ => {
+                let mut field = None;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    // 字段前缀: 字母组成的名称紧跟冒号，且冒号后不是空白
+                    if chars[i] == ':' && i > start && chars[start..i].iter().all(|c| c.is_ascii_alphabetic()) {
+                        let name: String = chars[start..i].iter().collect::<String>().to_lowercase();
+                        // 网址 (如 https://) 与未知前缀 (如 TODO:、note:) 不是字段，按普通词语处理
+                        if !FIELD_NAMES.contains(&name.as_str()) || chars[i + 1..].starts_with(&['/', '/']) {
+                            i += 1;
+                            continue;
+                        }
+                        if chars.get(i + 1).is_none_or(|n| n.is_whitespace() || *n == ')') {
+                            return Err(syntax_error(start, i + 1, format!("字段 '{}:' 后缺少搜索内容", name)));
+                        }
+                        i += 1;
+                        field = Some(name);
+                        break;
+                    }
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let kind = match (field, text.as_str()) {
+                    (Some(name), _) => TokenKind::Field(name),
+                    (None, "AND") => TokenKind::And,
+                    (None, "OR") => TokenKind::Or,
+                    (None, "NOT") => TokenKind::Not,
+                    (None, _) => TokenKind::Word(text),
+                };
+                tokens.push(Token { kind, start, end: i });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// ============================================================================
+// 2. 语法分析
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Default, // 标题与正文
+    Title,
+    Content,
+    Tag,
+    Path,
+    Modified,
+}
+
+impl Scope {
+    fn from_name(name: &str) -> Scope {
+        match name {
+            "title" => Scope::Title,
+            "content" => Scope::Content,
+            "tag" => Scope::Tag,
+            "path" => Scope::Path,
+            _ => Scope::Modified,
+        }
+    }
+}
+
+/// 修改时间范围 (Unix 秒)，[from, to)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeRange {
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text { scope: Scope, text: String },
+    Tag(String),
+    Path(String),
+    Modified(TimeRange),
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+}
+
+/// 解析后的搜索查询
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    root: Node,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    input_len: usize,
+}
+
+/// 日期的起止 (本地时间)：YYYY / YYYY-MM / YYYY-MM-DD
+fn date_period(text: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let parts: Vec<&str> = text.split('-').collect();
+    let numbers: Vec<u32> = parts.iter().map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [year] if parts[0].len() == 4 => {
+            let start = NaiveDate::from_ymd_opt(*year as i32, 1, 1)?;
+            Some((start, NaiveDate::from_ymd_opt(*year as i32 + 1, 1, 1)?))
+        }
+        [year, month] => {
+            let start = NaiveDate::from_ymd_opt(*year as i32, *month, 1)?;
+            let end = if *month == 12 {
+                NaiveDate::from_ymd_opt(*year as i32 + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(*year as i32, month + 1, 1)?
+            };
+            Some((start, end))
+        }
+        [year, month, day] => {
+            let start = NaiveDate::from_ymd_opt(*year as i32, *month, *day)?;
+            Some((start, start.succ_opt()?))
+        }
+        _ => None,
+    }
+}
+
+fn local_timestamp(date: NaiveDate) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|t| t.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp())
+}
+
+fn parse_time_range(value: &str) -> Option<TimeRange> {
+    let today = Local::now().date_naive();
+    let period = |text: &str| -> Option<(i64, i64)> {
+        let (start, end) = if text.eq_ignore_ascii_case("today") {
+            (today, today.succ_opt()?)
+        } else {
+            date_period(text)?
+        };
+        Some((local_timestamp(start), local_timestamp(end)))
+    };
+
+    if let Some((from, to)) = value.split_once("..") {
+        let from = if from.is_empty() { None } else { Some(period(from)?.0) };
+        let to = if to.is_empty() { None } else { Some(period(to)?.1) };
+        return Some(TimeRange { from, to });
+    }
+    for (op, take_start) in [(">=", true), ("<=", false), (">", false), ("<", true)] {
+        if let Some(rest) = value.strip_prefix(op) {
+            let (start, end) = period(rest)?;
+            return Some(match op {
+                ">=" | ">" => TimeRange { from: Some(if take_start { start } else { end }), to: None },
+                _ => TimeRange { from: None, to: Some(if take_start { start } else { end }) },
+            });
+        }
+    }
+    // 最近 N 天 / 周
+    let lower = value.to_lowercase();
+    if let Some(days) = lower
+        .strip_suffix('d')
+        .and_then(|n| n.parse::<i64>().ok())
+        .or_else(|| lower.strip_suffix('w').and_then(|n| n.parse::<i64>().ok()).map(|n| n * 7))
+    {
+        let start = today.checked_sub_signed(Duration::days(days.max(1) - 1))?;
+        return Some(TimeRange { from: Some(local_timestamp(start)), to: None });
+    }
+    let (from, to) = period(value)?;
+    Some(TimeRange { from: Some(from), to: Some(to) })
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.peek().map(|t| &t.kind)
+    }
+
+    fn end_error(&self, message: &str) -> SearchQueryError {
+        syntax_error(self.input_len, self.input_len + 1, message)
+    }
+
+    fn parse_or(&mut self, scope: Scope) -> Result<Node, SearchQueryError> {
+        let mut children = vec![self.parse_and(scope)?];
+        while self.peek_kind() == Some(&TokenKind::Or) {
+            self.pos += 1;
+            children.push(self.parse_and(scope)?);
+        }
+        Ok(if children.len() == 1 { children.remove(0) } else { Node::Or(children) })
+    }
+
+    /// AND 可以省略：相邻的条件默认同时满足
+    fn parse_and(&mut self, scope: Scope) -> Result<Node, SearchQueryError> {
+        let mut children = vec![self.parse_unary(scope)?];
+        loop {
+            match self.peek_kind() {
+                Some(TokenKind::And) => {
+                    self.pos += 1;
+                    children.push(self.parse_unary(scope)?);
+                }
+                Some(TokenKind::Or) | Some(TokenKind::RParen) | None => break,
+                Some(_) => children.push(self.parse_unary(scope)?),
+            }
+        }
+        Ok(if children.len() == 1 { children.remove(0) } else { Node::And(children) })
+    }
+
+    fn parse_unary(&mut self, scope: Scope) -> Result<Node, SearchQueryError> {
+        if matches!(self.peek_kind(), Some(TokenKind::Not) | Some(TokenKind::Minus)) {
+            self.pos += 1;
+            return Ok(Node::Not(Box::new(self.parse_unary(scope)?)));
+        }
+        self.parse_primary(scope)
+    }
+
+    fn parse_primary(&mut self, scope: Scope) -> Result<Node, SearchQueryError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.end_error("查询意外结束，缺少搜索内容"));
+        };
+        self.pos += 1;
+        match token.kind {
+            TokenKind::LParen => {
+                if self.peek_kind() == Some(&TokenKind::RParen) {
+                    return Err(syntax_error(token.start, token.end + 1, "括号中缺少搜索内容"));
+                }
+                let inner = self.parse_or(scope)?;
+                match self.peek() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => {
+                        self.pos += 1;
+                        Ok(inner)
+                    }
+                    _ => Err(syntax_error(token.start, token.end, "左括号 '(' 缺少对应的右括号 ')'")),
+                }
+            }
+            TokenKind::Field(name) => {
+                if scope != Scope::Default {
+                    return Err(syntax_error(token.start, token.end, "字段不能嵌套使用"));
+                }
+                self.parse_primary(Scope::from_name(&name))
+            }
+            TokenKind::Word(ref text) | TokenKind::Phrase(ref text) => self.value_node(scope, text, &token),
+            TokenKind::RParen => Err(syntax_error(token.start, token.end, "多余的右括号 ')'")),
+            TokenKind::And | TokenKind::Or | TokenKind::Not | TokenKind::Minus => {
+                let keyword = match token.kind {
+                    TokenKind::And => "AND",
+                    TokenKind::Or => "OR",
+                    _ => "NOT",
+                };
+                Err(syntax_error(token.start, token.end, format!("{} 前后缺少搜索内容", keyword)))
+            }
+        }
+    }
+
+    fn value_node(&self, scope: Scope, text: &str, token: &Token) -> Result<Node, SearchQueryError> {
+        match scope {
+            Scope::Tag => {
+                let tag = text.trim().trim_start_matches('#');
+                if tag.is_empty() {
+                    return Err(syntax_error(token.start, token.end, "tag: 后缺少标签名"));
+                }
+                Ok(Node::Tag(tag.to_string()))
+            }
+            Scope::Path => Ok(Node::Path(text.to_string())),
+            Scope::Modified => parse_time_range(text.trim()).map(Node::Modified).ok_or_else(|| {
+                syntax_error(
+                    token.start,
+                    token.end,
+                    format!("无法识别的日期 '{}'，应为 2024-03-01、2024-03、>2024-01-01、2024-01..2024-03 或 7d", text),
+                )
+            }),
+            _ => Ok(Node::Text { scope, text: text.to_string() }),
+        }
+    }
+}
+
+/// 按上面的语法解析搜索查询
+pub fn parse_search_query(input: &str) -> Result<SearchQuery, SearchQueryError> {
+    let tokens = tokenize(input)?;
+    let input_len = input.chars().count();
+    if tokens.is_empty() {
+        return Err(syntax_error(0, input_len.max(1), "查询为空"));
+    }
+    let mut parser = Parser { tokens, pos: 0, input_len };
+    let root = parser.parse_or(Scope::Default)?;
+    if let Some(token) = parser.peek() {
+        return Err(syntax_error(token.start, token.end, "多余的右括号 ')'"));
+    }
+    Ok(SearchQuery { root })
+}
+
+// ============================================================================
+// 3. 转换为 Tantivy 查询
+// ============================================================================
+
+/// 把查询条件转换为 Tantivy 查询所需的上下文
+/// conn 用于 tag: / path: / modified: 等不在索引中的字段 (先在数据库中查出笔记路径)
+pub struct QueryContext<'a> {
+    pub index: &'a Index,
+    pub fields: &'a SchemaFields,
+    pub conn: Option<&'a Connection>,
+    pub title_boost: f32,
+}
+
+impl QueryContext<'_> {
+    fn conn(&self) -> Result<&Connection> {
+        self.conn.ok_or_else(|| anyhow::anyhow!("数据库未初始化，无法按标签、路径或修改时间搜索"))
+    }
+
+    /// 按字段分词器切分文本：一个词生成词项查询，多个词生成短语查询；全是停用词时返回 None
+    fn text_query(&self, field: Field, text: &str) -> Result<Option<Box<dyn Query>>> {
+        let mut analyzer = self.index.tokenizer_for_field(field)?;
+        let mut terms: Vec<(usize, Term)> = Vec::new();
+        analyzer.token_stream(text).process(&mut |token| {
+            terms.push((token.position, Term::from_field_text(field, &token.text)));
+        });
+        Ok(match terms.len() {
+            0 => None,
+            1 => Some(Box::new(TermQuery::new(terms.remove(0).1, IndexRecordOption::WithFreqs))),
+            _ => Some(Box::new(PhraseQuery::new_with_offset(terms))),
+        })
+    }
+
+    fn paths_query(&self, paths: Vec<String>) -> Box<dyn Query> {
+        if paths.is_empty() {
+            return Box::new(EmptyQuery);
+        }
+        let field = self.fields.path;
+        Box::new(TermSetQuery::new(paths.into_iter().map(|p| Term::from_field_text(field, &p))))
+    }
+
+    fn query_paths(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<String>> {
+        let mut stmt = self.conn()?.prepare(sql)?;
+        let paths = stmt
+            .query_map(params, |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(paths)
+    }
+
+    fn compile(&self, node: &Node) -> Result<Option<Box<dyn Query>>> {
+        Ok(match node {
+            Node::Text { scope: Scope::Title, text } => self.text_query(self.fields.title, text)?,
+            Node::Text { scope: Scope::Content, text } => self.text_query(self.fields.content, text)?,
+            Node::Text { text, .. } => {
+                let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+                if let Some(title) = self.text_query(self.fields.title, text)? {
+                    clauses.push((Occur::Should, Box::new(BoostQuery::new(title, self.title_boost))));
+                }
+                if let Some(content) = self.text_query(self.fields.content, text)? {
+                    clauses.push((Occur::Should, content));
+                }
+                (!clauses.is_empty()).then(|| Box::new(BooleanQuery::new(clauses)) as Box<dyn Query>)
+            }
+            Node::Tag(tag) => Some(self.paths_query(paths_with_tag(self.conn()?, tag).map_err(anyhow::Error::msg)?)),
+            Node::Path(pattern) => {
+                let paths = self.query_paths(
+                    "SELECT path FROM files WHERE COALESCE(is_dir, 0) = 0 AND path LIKE ?1 ESCAPE '\\'",
+                    params![path_like_pattern(pattern)],
+                )?;
+                Some(self.paths_query(paths))
+            }
+            Node::Modified(range) => {
+                let paths = self.query_paths(
+                    "SELECT path FROM files WHERE COALESCE(is_dir, 0) = 0
+                       AND (?1 IS NULL OR last_modified >= ?1) AND (?2 IS NULL OR last_modified < ?2)",
+                    params![range.from, range.to],
+                )?;
+                Some(self.paths_query(paths))
+            }
+            Node::And(children) => {
+                let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+                for child in children {
+                    match child {
+                        Node::Not(inner) => {
+                            if let Some(query) = self.compile(inner)? {
+                                clauses.push((Occur::MustNot, query));
+                            }
+                        }
+                        _ => {
+                            if let Some(query) = self.compile(child)? {
+                                clauses.push((Occur::Must, query));
+                            }
+                        }
+                    }
+                }
+                with_positive_clause(clauses)
+            }
+            Node::Or(children) => {
+                let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+                for child in children {
+                    if let Some(query) = self.compile(child)? {
+                        clauses.push((Occur::Should, query));
+                    }
+                }
+                (!clauses.is_empty()).then(|| Box::new(BooleanQuery::new(clauses)) as Box<dyn Query>)
+            }
+            Node::Not(inner) => match self.compile(inner)? {
+                Some(query) => with_positive_clause(vec![(Occur::MustNot, query)]),
+                None => None,
+            },
+        })
+    }
+}
+
+/// path: 的值转换为 LIKE 模式 (配合 ESCAPE '\')：含 * 时 * 匹配任意字符，否则匹配路径中的任意位置
+fn path_like_pattern(pattern: &str) -> String {
+    let escaped = escape_like(pattern);
+    if escaped.contains('*') {
+        escaped.replace('*', "%")
+    } else {
+        format!("%{}%", escaped)
+    }
+}
+
+/// 只有排除条件的组合匹配不到任何文档，此时以“全部笔记”为基础再排除
+fn with_positive_clause(mut clauses: Vec<(Occur, Box<dyn Query>)>) -> Option<Box<dyn Query>> {
+    if clauses.is_empty() {
+        return None;
+    }
+    if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+        clauses.push((Occur::Must, Box::new(AllQuery)));
+    }
+    Some(Box::new(BooleanQuery::new(clauses)))
+}
+
+/// 普通文本条件及其搜索范围 (标题 / 正文)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlainText {
+    pub text: String,
+    pub title: bool,
+    pub content: bool,
+}
+
+impl SearchQuery {
+    /// 转换为 Tantivy 查询；所有词都被分词器过滤掉 (如只有停用词) 时返回 None
+    pub fn to_query(&self, ctx: &QueryContext) -> Result<Option<Box<dyn Query>>> {
+        ctx.compile(&self.root)
+    }
+
+    /// 查询只由普通文本条件 (不带字段、title:、content:) 经 AND / OR 组合而成时返回这些文本；
+    /// 含有排除条件或 tag: / path: / modified: 时返回 None
+    pub fn plain_texts(&self) -> Option<Vec<PlainText>> {
+        fn collect(node: &Node, texts: &mut Vec<PlainText>) -> bool {
+            match node {
+                Node::Text { scope, text } => {
+                    texts.push(PlainText {
+                        text: text.clone(),
+                        title: *scope != Scope::Content,
+                        content: *scope != Scope::Title,
+                    });
+                    true
+                }
+                Node::And(children) | Node::Or(children) => children.iter().all(|child| collect(child, texts)),
+                _ => false,
+            }
+        }
+        let mut texts = Vec::new();
+        collect(&self.root, &mut texts).then_some(texts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Node {
+        parse_search_query(input).unwrap_or_else(|e| panic!("{}: {}", input, e)).root
+    }
+
+    fn error(input: &str) -> SearchQueryError {
+        parse_search_query(input).expect_err(input)
+    }
+
+    fn text(scope: Scope, text: &str) -> Node {
+        Node::Text { scope, text: text.to_string() }
+    }
+
+    fn word(value: &str) -> Node {
+        text(Scope::Default, value)
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> i64 {
+        local_timestamp(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+    }
+
+    fn range(from: Option<i64>, to: Option<i64>) -> Option<TimeRange> {
+        Some(TimeRange { from, to })
+    }
+
+    #[test]
+    fn adjacent_terms_are_and() {
+        assert_eq!(parse("rust 所有权"), Node::And(vec![word("rust"), word("所有权")]));
+        assert_eq!(parse("\"borrow checker\""), word("borrow checker"));
+        assert_eq!(parse("\"say \\\"hi\\\"\""), word("say \"hi\""));
+    }
+
+    #[test]
+    fn precedence_is_not_and_or() {
+        assert_eq!(
+            parse("a OR b c"),
+            Node::Or(vec![word("a"), Node::And(vec![word("b"), word("c")])])
+        );
+        assert_eq!(
+            parse("a AND b OR c"),
+            Node::Or(vec![Node::And(vec![word("a"), word("b")]), word("c")])
+        );
+        assert_eq!(
+            parse("NOT a b"),
+            Node::And(vec![Node::Not(Box::new(word("a"))), word("b")])
+        );
+        assert_eq!(
+            parse("(rust OR go) -java"),
+            Node::And(vec![
+                Node::Or(vec![word("rust"), word("go")]),
+                Node::Not(Box::new(word("java"))),
+            ])
+        );
+        // 小写的 and / or 是普通词语
+        assert_eq!(parse("a or b"), Node::And(vec![word("a"), word("or"), word("b")]));
+    }
+
+    #[test]
+    fn minus_negates_only_before_a_term() {
+        assert_eq!(parse("-tag:archive"), Node::Not(Box::new(Node::Tag("archive".to_string()))));
+        assert_eq!(parse("-(a OR b)"), Node::Not(Box::new(Node::Or(vec![word("a"), word("b")]))));
+        assert_eq!(parse("a - b"), Node::And(vec![word("a"), word("-"), word("b")]));
+        assert_eq!(parse("e-mail"), word("e-mail"));
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(parse("title:rust"), text(Scope::Title, "rust"));
+        assert_eq!(parse("Content:\"error handling\""), text(Scope::Content, "error handling"));
+        assert_eq!(
+            parse("title:(rust OR go)"),
+            Node::Or(vec![text(Scope::Title, "rust"), text(Scope::Title, "go")])
+        );
+        assert_eq!(parse("tag:#project/a"), Node::Tag("project/a".to_string()));
+        assert_eq!(parse("path:daily/"), Node::Path("daily/".to_string()));
+        assert!(matches!(parse("modified:7d"), Node::Modified(_)));
+    }
+
+    #[test]
+    fn unknown_prefixes_and_urls_are_words() {
+        assert_eq!(parse("TODO: fix"), Node::And(vec![word("TODO:"), word("fix")]));
+        assert_eq!(parse("note:"), word("note:"));
+        assert_eq!(parse("foo:bar"), word("foo:bar"));
+        assert_eq!(parse("https://example.com"), word("https://example.com"));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_problem() {
+        let e = error("(rust go");
+        assert_eq!((e.start, e.end), (0, 1));
+        assert_eq!(error("rust )").start, 5);
+        assert_eq!(error("\"abc").start, 0);
+        assert_eq!(error("rust OR").start, 7);
+        assert_eq!(error("AND rust").start, 0);
+        assert_eq!(error("()").start, 0);
+        assert_eq!(error("title:").start, 0);
+        assert_eq!(error("title:(tag:x)").start, 7);
+        assert_eq!(error("tag:#").start, 4);
+        let e = error("modified:yesterdayish");
+        assert_eq!((e.start, e.end), (9, 21));
+        assert!(parse_search_query("   ").is_err());
+    }
+
+    #[test]
+    fn time_periods() {
+        assert_eq!(parse_time_range("2024"), range(Some(date(2024, 1, 1)), Some(date(2025, 1, 1))));
+        assert_eq!(parse_time_range("2024-03"), range(Some(date(2024, 3, 1)), Some(date(2024, 4, 1))));
+        assert_eq!(parse_time_range("2024-12"), range(Some(date(2024, 12, 1)), Some(date(2025, 1, 1))));
+        assert_eq!(parse_time_range("2024-02-29"), range(Some(date(2024, 2, 29)), Some(date(2024, 3, 1))));
+        for invalid in ["24", "2024-13", "2023-02-29", "2024-1-1-1", "yesterday", ""] {
+            assert_eq!(parse_time_range(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn time_comparisons_and_ranges() {
+        assert_eq!(parse_time_range(">=2024-01-01"), range(Some(date(2024, 1, 1)), None));
+        assert_eq!(parse_time_range(">2024-01-01"), range(Some(date(2024, 1, 2)), None));
+        assert_eq!(parse_time_range("<2024-01"), range(None, Some(date(2024, 1, 1))));
+        assert_eq!(parse_time_range("<=2024-01"), range(None, Some(date(2024, 2, 1))));
+        assert_eq!(
+            parse_time_range("2024-01..2024-03"),
+            range(Some(date(2024, 1, 1)), Some(date(2024, 4, 1)))
+        );
+        assert_eq!(parse_time_range("2024-06.."), range(Some(date(2024, 6, 1)), None));
+        assert_eq!(parse_time_range("..2024"), range(None, Some(date(2025, 1, 1))));
+        assert_eq!(parse_time_range("2024..x"), None);
+    }
+
+    #[test]
+    fn relative_times() {
+        let today = Local::now().date_naive();
+        let days_ago = |n: i64| local_timestamp(today - Duration::days(n));
+        assert_eq!(parse_time_range("today"), range(Some(days_ago(0)), Some(days_ago(-1))));
+        assert_eq!(parse_time_range("1d"), range(Some(days_ago(0)), None));
+        assert_eq!(parse_time_range("7D"), range(Some(days_ago(6)), None));
+        assert_eq!(parse_time_range("2w"), range(Some(days_ago(13)), None));
+        assert_eq!(parse_time_range(">=today"), range(Some(days_ago(0)), None));
+    }
+
+    #[test]
+    fn path_patterns_escape_like_wildcards() {
+        assert_eq!(path_like_pattern("daily/"), "%daily/%");
+        assert_eq!(path_like_pattern("*.md"), "%.md");
+        assert_eq!(path_like_pattern("a_b%c\\d"), "%a\\_b\\%c\\\\d%");
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE files (path TEXT); INSERT INTO files VALUES ('a_b/x.md'), ('axb/y.md'), ('100%/z.md');")
+            .unwrap();
+        let matching = |pattern: &str| -> Vec<String> {
+            let mut stmt = conn.prepare("SELECT path FROM files WHERE path LIKE ?1 ESCAPE '\\' ORDER BY path").unwrap();
+            let paths = stmt.query_map(params![path_like_pattern(pattern)], |row| row.get(0)).unwrap();
+            paths.collect::<rusqlite::Result<_>>().unwrap()
+        };
+        assert_eq!(matching("a_b/"), vec!["a_b/x.md"]);
+        assert_eq!(matching("100%"), vec!["100%/z.md"]);
+        assert_eq!(matching("a*/*.md"), vec!["a_b/x.md", "axb/y.md"]);
+    }
+
+    #[test]
+    fn plain_texts_reject_exclusions_and_filters() {
+        let texts = parse_search_query("a title:b (c OR content:\"d e\")").unwrap().plain_texts().unwrap();
+        let scopes: Vec<(&str, bool, bool)> = texts.iter().map(|t| (t.text.as_str(), t.title, t.content)).collect();
+        assert_eq!(scopes, vec![("a", true, true), ("b", true, false), ("c", true, true), ("d e", false, true)]);
+        for query in ["a -b", "NOT a", "tag:x", "a path:daily/", "modified:7d", "a (b OR -c)"] {
+            assert_eq!(parse_search_query(query).unwrap().plain_texts(), None, "{}", query);
+        }
+    }
+}
@@ -118,7 +118,7 @@ export const TauriAPI = {
 
     // 工具
     utils: {
-        parseMarkdown: (content) => invoke('parse_markdown', { content }),
+        parseMarkdown: (content, relativePath = null) => invoke('parse_markdown', { content, relativePath }),
         checkIndexingStatus: () => invoke('check_indexing_status')
     }
 };